@@ -0,0 +1,346 @@
+// Times the hand evaluator over fixed sets of random hands.
+//
+//     cargo bench                  all benchmarks
+//     cargo bench -- best_of       only those whose name contains `best_of`
+//
+// Results are written to `bench_output.txt`, and the next run prints how much each median
+// moved against it, so checking out another commit and running again compares the two.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use book_examples::deck::{Deck, Rng};
+use book_examples::video_poker::Paytable;
+use book_examples::{Card, PokerHand, PokerHandType, eval, index};
+
+// The same seed on every run, so every commit is timed on the same hands.
+const SEED: u64 = 46;
+const HANDS: usize = 1_000;
+const WARM_UP: Duration = Duration::from_millis(300);
+const SAMPLES: usize = 30;
+const SAMPLE_TIME: Duration = Duration::from_millis(30);
+const OUTPUT: &str = "bench_output.txt";
+
+struct Bench {
+    filter: Option<String>,
+    previous: Vec<(String, String)>,
+    lines: Vec<(String, String)>,
+}
+
+impl Bench {
+    fn new(filter: Option<String>) -> Self {
+        let previous = fs::read_to_string(OUTPUT)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let name = line.split_whitespace().next()?;
+                Some((name.to_string(), line.to_string()))
+            })
+            .collect();
+        Bench {
+            filter,
+            previous,
+            lines: Vec::new(),
+        }
+    }
+
+    // Runs `routine` over a whole hand set of `hands` hands at a time and reports the time per
+    // hand. `setup` makes the input of every pass and is not timed.
+    fn run<I, O>(
+        &mut self,
+        name: &str,
+        hands: usize,
+        mut setup: impl FnMut() -> I,
+        mut routine: impl FnMut(I) -> O,
+    ) {
+        if self
+            .filter
+            .as_ref()
+            .is_some_and(|filter| !name.contains(filter.as_str()))
+        {
+            return;
+        }
+
+        let mut pass = || {
+            let input = setup();
+            let start = Instant::now();
+            black_box(routine(black_box(input)));
+            start.elapsed()
+        };
+
+        // Warm up, and find out how many passes fill a sample.
+        let start = Instant::now();
+        let (mut passes, mut spent) = (0, Duration::ZERO);
+        while start.elapsed() < WARM_UP {
+            spent += pass();
+            passes += 1;
+        }
+        let per_sample = (SAMPLE_TIME.as_secs_f64() * passes as f64 / spent.as_secs_f64())
+            .ceil()
+            .max(1.0) as u32;
+
+        let mut times: Vec<f64> = (0..SAMPLES)
+            .map(|_| {
+                let spent: Duration = (0..per_sample).map(|_| pass()).sum();
+                spent.as_secs_f64() * 1e9 / (per_sample as f64 * hands as f64)
+            })
+            .collect();
+        times.sort_by(f64::total_cmp);
+        let median = times[SAMPLES / 2];
+
+        let mut line = format!(
+            "{name:<26} time: [{:.1} ns {median:.1} ns {:.1} ns]",
+            times[0],
+            times[SAMPLES - 1]
+        );
+        println!("{line}");
+        if let Some(before) = self.previous_median(name) {
+            println!(
+                "{:<26} change: {:+.1}% against {OUTPUT}",
+                "",
+                100.0 * (median / before - 1.0)
+            );
+        }
+        write!(line, " per hand").unwrap();
+        self.lines.push((name.to_string(), line));
+    }
+
+    fn previous_median(&self, name: &str) -> Option<f64> {
+        let (_, line) = self
+            .previous
+            .iter()
+            .find(|(previous, _)| previous == name)?;
+        // name time: [low ns median ns high ns]
+        line.split_whitespace().nth(4)?.parse().ok()
+    }
+
+    // Keeps the earlier results of benchmarks that were filtered out.
+    fn save(self) {
+        let mut output = String::new();
+        for (name, line) in &self.previous {
+            if !self.lines.iter().any(|(ran, _)| ran == name) {
+                writeln!(output, "{line}").unwrap();
+            }
+        }
+        for (_, line) in &self.lines {
+            writeln!(output, "{line}").unwrap();
+        }
+        if let Err(error) = fs::write(OUTPUT, output) {
+            eprintln!("Unable to write {OUTPUT}: {error}");
+        }
+    }
+}
+
+fn deal(rng: &mut Rng, count: usize, cards: usize) -> Vec<Vec<Card>> {
+    (0..count)
+        .map(|_| {
+            let mut deck = Deck::new();
+            deck.shuffle(rng);
+            (0..cards).map(|_| deck.deal().unwrap()).collect()
+        })
+        .collect()
+}
+
+fn unsorted(cards: &[Card]) -> PokerHand {
+    let mut hand = PokerHand::new();
+    for &card in cards {
+        hand.add_card(card).unwrap();
+    }
+    hand
+}
+
+// What `PokerHand` does to count ranks, copied here since it is private.
+fn hash_map_histogram(cards: &[Card]) -> Vec<(u8, usize)> {
+    let mut map = HashMap::new();
+    for card in cards {
+        map.entry(card.rank())
+            .and_modify(|count| *count += 1)
+            .or_insert(1);
+    }
+
+    let mut results: Vec<(u8, usize)> = map.into_iter().collect();
+    results.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(&b.0)).reverse());
+    results
+}
+
+// The same counts without the allocations, to see what the `HashMap` costs.
+fn array_histogram(cards: &[Card]) -> [(u8, usize); 5] {
+    let mut counts = [0; 15];
+    for card in cards {
+        counts[card.rank() as usize] += 1;
+    }
+
+    let mut results = [(0, 0); 5];
+    let mut len = 0;
+    for rank in (2..=14).rev() {
+        if counts[rank] > 0 {
+            results[len] = (rank as u8, counts[rank]);
+            len += 1;
+        }
+    }
+    results[..len].sort_by_key(|&(_, count)| Reverse(count));
+    results
+}
+
+fn main() {
+    let filter = std::env::args().skip(1).find(|arg| !arg.starts_with('-'));
+    let mut bench = Bench::new(filter);
+
+    let mut rng = Rng::new(SEED);
+    let fives = deal(&mut rng, HANDS, 5);
+    let sevens = deal(&mut rng, HANDS, 7);
+    let unsorted_hands: Vec<PokerHand> = fives.iter().map(|cards| unsorted(cards)).collect();
+    let sorted_hands: Vec<PokerHand> = fives
+        .iter()
+        .map(|cards| PokerHand::best_of(cards).unwrap())
+        .collect();
+
+    bench.run(
+        "rank_histogram/hash_map",
+        HANDS,
+        || (),
+        |()| {
+            fives
+                .iter()
+                .map(|cards| hash_map_histogram(cards).len())
+                .sum::<usize>()
+        },
+    );
+    bench.run(
+        "rank_histogram/array",
+        HANDS,
+        || (),
+        |()| {
+            fives
+                .iter()
+                .map(|cards| array_histogram(cards)[0].1)
+                .sum::<usize>()
+        },
+    );
+
+    let mut typed = unsorted_hands.clone();
+    bench.run(
+        "set_hand_type",
+        HANDS,
+        || (),
+        |()| {
+            for hand in typed.iter_mut() {
+                hand.set_hand_type().unwrap();
+            }
+        },
+    );
+    bench.run(
+        "sort_hand",
+        HANDS,
+        || unsorted_hands.clone(),
+        |mut hands| {
+            for hand in hands.iter_mut() {
+                hand.sort_hand().unwrap();
+            }
+            hands
+        },
+    );
+    bench.run(
+        "cmp",
+        HANDS - 1,
+        || (),
+        |()| {
+            sorted_hands
+                .windows(2)
+                .filter(|pair| pair[0] > pair[1])
+                .count()
+        },
+    );
+    bench.run(
+        "best_of/5_cards",
+        HANDS,
+        || (),
+        |()| {
+            fives
+                .iter()
+                .map(|cards| PokerHand::best_of(cards).unwrap().cards().len())
+                .sum::<usize>()
+        },
+    );
+    bench.run(
+        "best_of/7_cards",
+        HANDS,
+        || (),
+        |()| {
+            sevens
+                .iter()
+                .map(|cards| PokerHand::best_of(cards).unwrap().cards().len())
+                .sum::<usize>()
+        },
+    );
+
+    // The allocation-free path underneath `PokerHand`.
+    let arrays: Vec<[Card; 5]> = fives
+        .iter()
+        .map(|cards| cards.as_slice().try_into().unwrap())
+        .collect();
+    bench.run(
+        "eval::evaluate",
+        HANDS,
+        || (),
+        |()| {
+            arrays
+                .iter()
+                .filter(|cards| eval::evaluate(cards).hand_type() == &PokerHandType::HighCard)
+                .count()
+        },
+    );
+    bench.run(
+        "eval::best_of/7_cards",
+        HANDS,
+        || (),
+        |()| {
+            sevens
+                .iter()
+                .filter(|cards| eval::best_of(cards).is_some())
+                .count()
+        },
+    );
+
+    // Lookups: hand indexes and the video poker pattern table.
+    bench.run(
+        "index_of/5_cards",
+        HANDS,
+        || (),
+        |()| {
+            fives
+                .iter()
+                .map(|cards| index::index_of(cards).unwrap())
+                .max()
+        },
+    );
+    bench.run(
+        "canonical_index_of/7_cards",
+        HANDS,
+        || (),
+        |()| {
+            sevens
+                .iter()
+                .map(|cards| index::canonical_index_of(cards).unwrap())
+                .max()
+        },
+    );
+    let paytable = Paytable::jacks_or_better();
+    bench.run(
+        "payout/jacks_or_better",
+        HANDS,
+        || (),
+        |()| {
+            fives
+                .iter()
+                .map(|cards| paytable.payout(cards).unwrap())
+                .sum::<u32>()
+        },
+    );
+
+    bench.save();
+}
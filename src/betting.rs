@@ -0,0 +1,509 @@
+use std::fmt;
+
+use crate::holdem::{Action, LegalAction, Street};
+
+/// Everything a betting structure needs to know about the spot the player to act is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BettingState {
+    pub street: Street,
+    pub big_blind: u32,
+    /// Every chip put in so far, the bets on the current street included.
+    pub pot: u32,
+    /// The amount everyone has to match on this street.
+    pub current_bet: u32,
+    /// Size of the last full bet or raise on this street.
+    pub min_raise: u32,
+    /// Full bets and raises made on this street. The big blind counts as the first preflop.
+    pub bets_this_street: u32,
+    /// What the player to act already has in front of them on this street.
+    pub street_bet: u32,
+    pub stack: u32,
+    /// `false` once the player acted and no full raise re-opened the betting since.
+    pub can_raise: bool,
+}
+
+impl BettingState {
+    pub fn to_call(&self) -> u32 {
+        (self.current_bet - self.street_bet).min(self.stack)
+    }
+
+    /// The most the player can have in front of them for the street.
+    pub fn all_in(&self) -> u32 {
+        self.street_bet + self.stack
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionError {
+    NoActionPending,
+    CannotCheck { to_call: u32 },
+    NothingToCall,
+    CannotBetFacingBet { current_bet: u32 },
+    NothingToRaise,
+    RaiseNotReopened,
+    NotEnoughChipsToRaise { all_in: u32, current_bet: u32 },
+    BettingCapped { bets: u32 },
+    BelowMinimum { amount: u32, min: u32 },
+    AboveMaximum { amount: u32, max: u32 },
+    Table(&'static str),
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionError::NoActionPending => write!(f, "no one is left to act"),
+            ActionError::CannotCheck { to_call } => {
+                write!(f, "cannot check when facing a bet, {to_call} to call")
+            }
+            ActionError::NothingToCall => write!(f, "nothing to call, check instead"),
+            ActionError::CannotBetFacingBet { current_bet } => {
+                write!(
+                    f,
+                    "cannot bet when facing a bet of {current_bet}, raise instead"
+                )
+            }
+            ActionError::NothingToRaise => write!(f, "nothing to raise, bet instead"),
+            ActionError::RaiseNotReopened => {
+                write!(
+                    f,
+                    "the betting was not re-opened by a full raise, call or fold"
+                )
+            }
+            ActionError::NotEnoughChipsToRaise {
+                all_in,
+                current_bet,
+            } => write!(
+                f,
+                "all in for {all_in} does not raise the current bet of {current_bet}"
+            ),
+            ActionError::BettingCapped { bets } => {
+                write!(f, "betting is capped after {bets} bets and raises")
+            }
+            ActionError::BelowMinimum { amount, min } => {
+                write!(f, "{amount} is below the minimum of {min}")
+            }
+            ActionError::AboveMaximum { amount, max } => {
+                write!(f, "{amount} is above the maximum of {max}")
+            }
+            ActionError::Table(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for ActionError {}
+
+/// The rules for how much a player may bet or raise.
+pub trait BettingStructure: fmt::Debug {
+    /// The largest total a player may bet or raise to, ignoring their stack.
+    fn max_bet(&self, state: &BettingState) -> u32;
+
+    /// The smallest total a full bet or raise has to reach, ignoring their stack.
+    fn min_bet(&self, state: &BettingState) -> u32 {
+        if state.current_bet == 0 {
+            state.big_blind
+        } else {
+            state.current_bet + state.min_raise
+        }
+    }
+
+    /// Number of bets and raises allowed on a street, `None` for no cap.
+    fn cap(&self) -> Option<u32> {
+        None
+    }
+
+    fn legal_actions(&self, state: &BettingState) -> Vec<LegalAction> {
+        let mut actions = vec![LegalAction::Fold];
+        if state.current_bet == state.street_bet {
+            actions.push(LegalAction::Check);
+        } else {
+            actions.push(LegalAction::Call(state.to_call()));
+        }
+
+        if raise_error(self, state).is_none() {
+            let all_in = state.all_in();
+            let min = self.min_bet(state).min(all_in);
+            let max = self.max_bet(state).max(min).min(all_in);
+            if state.current_bet == 0 {
+                actions.push(LegalAction::Bet { min, max });
+            } else {
+                actions.push(LegalAction::Raise { min, max });
+            }
+        }
+
+        actions
+    }
+
+    fn validate(&self, state: &BettingState, action: Action) -> Result<(), ActionError> {
+        match action {
+            Action::Fold => Ok(()),
+            Action::Check if state.current_bet > state.street_bet => {
+                Err(ActionError::CannotCheck {
+                    to_call: state.to_call(),
+                })
+            }
+            Action::Check => Ok(()),
+            Action::Call if state.current_bet == state.street_bet => {
+                Err(ActionError::NothingToCall)
+            }
+            Action::Call => Ok(()),
+            Action::Bet(_) if state.current_bet > 0 => Err(ActionError::CannotBetFacingBet {
+                current_bet: state.current_bet,
+            }),
+            Action::Raise(_) if state.current_bet == 0 => Err(ActionError::NothingToRaise),
+            Action::Bet(amount) | Action::Raise(amount) => {
+                if let Some(error) = raise_error(self, state) {
+                    return Err(error);
+                }
+
+                let (min, max) = self
+                    .legal_actions(state)
+                    .into_iter()
+                    .find_map(|legal| match legal {
+                        LegalAction::Bet { min, max } | LegalAction::Raise { min, max } => {
+                            Some((min, max))
+                        }
+                        _ => None,
+                    })
+                    .expect("A bet or raise is legal when there is no raise error");
+                if amount < min {
+                    return Err(ActionError::BelowMinimum { amount, min });
+                }
+                if amount > max {
+                    return Err(ActionError::AboveMaximum { amount, max });
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// Why the player cannot put in a bet or raise at all, if they cannot.
+fn raise_error<S: BettingStructure + ?Sized>(
+    structure: &S,
+    state: &BettingState,
+) -> Option<ActionError> {
+    if state.current_bet > 0 && !state.can_raise {
+        return Some(ActionError::RaiseNotReopened);
+    }
+    if state.all_in() <= state.current_bet {
+        return Some(ActionError::NotEnoughChipsToRaise {
+            all_in: state.all_in(),
+            current_bet: state.current_bet,
+        });
+    }
+    match structure.cap() {
+        Some(cap) if state.bets_this_street >= cap => {
+            Some(ActionError::BettingCapped { bets: cap })
+        }
+        _ => None,
+    }
+}
+
+/// Any amount up to the whole stack. A raise has to be at least as big as the last one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NoLimit;
+
+impl BettingStructure for NoLimit {
+    fn max_bet(&self, _state: &BettingState) -> u32 {
+        u32::MAX
+    }
+}
+
+/// Raises are capped at the size of the pot after calling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PotLimit;
+
+impl BettingStructure for PotLimit {
+    fn max_bet(&self, state: &BettingState) -> u32 {
+        let to_call = state.current_bet - state.street_bet;
+        state.current_bet + state.pot + to_call
+    }
+}
+
+/// Bets and raises come in fixed sizes: the small bet before the flop and on the flop, the
+/// big bet on the turn and river. Only `cap` bets and raises are allowed on every street.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedLimit {
+    pub small_bet: u32,
+    pub big_bet: u32,
+    pub cap: u32,
+}
+
+impl FixedLimit {
+    pub fn bet_size(&self, street: Street) -> u32 {
+        match street {
+            Street::Preflop | Street::Flop => self.small_bet,
+            Street::Turn | Street::River | Street::Showdown => self.big_bet,
+        }
+    }
+}
+
+impl BettingStructure for FixedLimit {
+    fn min_bet(&self, state: &BettingState) -> u32 {
+        state.current_bet + self.bet_size(state.street)
+    }
+
+    fn max_bet(&self, state: &BettingState) -> u32 {
+        self.min_bet(state)
+    }
+
+    fn cap(&self) -> Option<u32> {
+        Some(self.cap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> BettingState {
+        BettingState {
+            street: Street::Flop,
+            big_blind: 2,
+            pot: 10,
+            current_bet: 0,
+            min_raise: 2,
+            bets_this_street: 0,
+            street_bet: 0,
+            stack: 100,
+            can_raise: true,
+        }
+    }
+
+    // Somebody bet 4 into a pot of 10, so 14 is in the middle with 4 to call.
+    fn facing_bet() -> BettingState {
+        BettingState {
+            pot: 14,
+            current_bet: 4,
+            min_raise: 4,
+            bets_this_street: 1,
+            ..state()
+        }
+    }
+
+    #[test]
+    fn betting_legal_actions() {
+        let fixed_limit = FixedLimit {
+            small_bet: 2,
+            big_bet: 4,
+            cap: 4,
+        };
+
+        let cases: Vec<(&str, &dyn BettingStructure, BettingState, Vec<LegalAction>)> = vec![
+            (
+                "No limit -- open for anything",
+                &NoLimit,
+                state(),
+                vec![
+                    LegalAction::Fold,
+                    LegalAction::Check,
+                    LegalAction::Bet { min: 2, max: 100 },
+                ],
+            ),
+            (
+                "No limit -- min raise is the last raise size",
+                &NoLimit,
+                facing_bet(),
+                vec![
+                    LegalAction::Fold,
+                    LegalAction::Call(4),
+                    LegalAction::Raise { min: 8, max: 100 },
+                ],
+            ),
+            (
+                "Pot limit -- bet the pot",
+                &PotLimit,
+                state(),
+                vec![
+                    LegalAction::Fold,
+                    LegalAction::Check,
+                    LegalAction::Bet { min: 2, max: 10 },
+                ],
+            ),
+            (
+                "Pot limit -- raise to the pot after calling",
+                &PotLimit,
+                facing_bet(),
+                vec![
+                    LegalAction::Fold,
+                    LegalAction::Call(4),
+                    LegalAction::Raise { min: 8, max: 22 },
+                ],
+            ),
+            (
+                "Pot limit -- short stack",
+                &PotLimit,
+                BettingState {
+                    stack: 6,
+                    ..facing_bet()
+                },
+                vec![
+                    LegalAction::Fold,
+                    LegalAction::Call(4),
+                    LegalAction::Raise { min: 6, max: 6 },
+                ],
+            ),
+            (
+                "Fixed limit -- small bet on the flop",
+                &fixed_limit,
+                facing_bet(),
+                vec![
+                    LegalAction::Fold,
+                    LegalAction::Call(4),
+                    LegalAction::Raise { min: 6, max: 6 },
+                ],
+            ),
+            (
+                "Fixed limit -- big bet on the turn",
+                &fixed_limit,
+                BettingState {
+                    street: Street::Turn,
+                    ..state()
+                },
+                vec![
+                    LegalAction::Fold,
+                    LegalAction::Check,
+                    LegalAction::Bet { min: 4, max: 4 },
+                ],
+            ),
+            (
+                "Fixed limit -- capped",
+                &fixed_limit,
+                BettingState {
+                    bets_this_street: 4,
+                    ..facing_bet()
+                },
+                vec![LegalAction::Fold, LegalAction::Call(4)],
+            ),
+            (
+                "Not re-opened after a short all in",
+                &NoLimit,
+                BettingState {
+                    can_raise: false,
+                    ..facing_bet()
+                },
+                vec![LegalAction::Fold, LegalAction::Call(4)],
+            ),
+        ];
+
+        for (name, structure, state, expected) in cases {
+            assert_eq!(structure.legal_actions(&state), expected, "Case: {name}");
+        }
+    }
+
+    #[test]
+    fn betting_validate() {
+        let fixed_limit = FixedLimit {
+            small_bet: 2,
+            big_bet: 4,
+            cap: 4,
+        };
+
+        type Case<'a> = (
+            &'a str,
+            &'a dyn BettingStructure,
+            BettingState,
+            Action,
+            Result<(), ActionError>,
+        );
+        let cases: Vec<Case> = vec![
+            ("Check", &NoLimit, state(), Action::Check, Ok(())),
+            (
+                "Check facing a bet",
+                &NoLimit,
+                facing_bet(),
+                Action::Check,
+                Err(ActionError::CannotCheck { to_call: 4 }),
+            ),
+            (
+                "Call with nothing to call",
+                &NoLimit,
+                state(),
+                Action::Call,
+                Err(ActionError::NothingToCall),
+            ),
+            (
+                "Bet facing a bet",
+                &NoLimit,
+                facing_bet(),
+                Action::Bet(10),
+                Err(ActionError::CannotBetFacingBet { current_bet: 4 }),
+            ),
+            (
+                "Raise with no bet",
+                &NoLimit,
+                state(),
+                Action::Raise(10),
+                Err(ActionError::NothingToRaise),
+            ),
+            (
+                "No limit -- below the min raise",
+                &NoLimit,
+                facing_bet(),
+                Action::Raise(7),
+                Err(ActionError::BelowMinimum { amount: 7, min: 8 }),
+            ),
+            (
+                "No limit -- more than the stack",
+                &NoLimit,
+                facing_bet(),
+                Action::Raise(101),
+                Err(ActionError::AboveMaximum {
+                    amount: 101,
+                    max: 100,
+                }),
+            ),
+            (
+                "Pot limit -- over the pot",
+                &PotLimit,
+                facing_bet(),
+                Action::Raise(23),
+                Err(ActionError::AboveMaximum {
+                    amount: 23,
+                    max: 22,
+                }),
+            ),
+            (
+                "Pot limit -- pot it",
+                &PotLimit,
+                facing_bet(),
+                Action::Raise(22),
+                Ok(()),
+            ),
+            (
+                "Fixed limit -- wrong size",
+                &fixed_limit,
+                facing_bet(),
+                Action::Raise(8),
+                Err(ActionError::AboveMaximum { amount: 8, max: 6 }),
+            ),
+            (
+                "Fixed limit -- capped",
+                &fixed_limit,
+                BettingState {
+                    bets_this_street: 4,
+                    ..facing_bet()
+                },
+                Action::Raise(6),
+                Err(ActionError::BettingCapped { bets: 4 }),
+            ),
+            (
+                "Not enough chips to raise",
+                &NoLimit,
+                BettingState {
+                    stack: 4,
+                    ..facing_bet()
+                },
+                Action::Raise(4),
+                Err(ActionError::NotEnoughChipsToRaise {
+                    all_in: 4,
+                    current_bet: 4,
+                }),
+            ),
+        ];
+
+        for (name, structure, state, action, expected) in cases {
+            assert_eq!(structure.validate(&state, action), expected, "Case: {name}");
+        }
+    }
+}
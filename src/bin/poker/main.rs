@@ -0,0 +1,492 @@
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write as _};
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use book_examples::deck::Deck;
+use book_examples::encoding;
+use book_examples::equity::equity;
+use book_examples::ofc::{self, OfcHand};
+use book_examples::preflop::{PreflopTable, StartingHand};
+use book_examples::simulation::Runner;
+use book_examples::{Card, PokerHand, parse_cards};
+
+mod play;
+mod repl;
+
+const USAGE: &str = "\
+Usage: poker <command> [arguments]
+
+Commands:
+  eval <cards>                      Best hand out of 5 to 7 cards
+  compare <cards> <cards>           Which of two hands wins
+  equity <hand> <hand>... [--board <cards>] [--samples <n>] [--seed <n>]
+                                    Hold'em equity of two or more hands
+  deal [--players <n>] [--seed <n>] Deals a hand of Hold'em
+  preflop <hand> <hand> --table <file>
+                                    Looks up preflop equity, hands written like AKs or QQ
+  preflop-table <file> [--samples <n>] [--seed <n>] [--threads <n>]
+                                    Works out the preflop equity table and saves it
+  ofc <hand> <hand>...              Scores Open-Face Chinese hands, rows split by \"/\"
+  repl [--samples <n>] [--seed <n>] Explores hands interactively
+  play [--session <file>] [--seed <n>]
+                                    Heads-up no-limit against a bot, saved after every hand
+  help                              Shows this message
+
+Cards are written like \"Ah Kh\", \"AhKh\" or \"Ah,Kh\".";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match run(&args) {
+        Ok(output) => {
+            print!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("error: {error}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<String, String> {
+    let Some((command, args)) = args.split_first() else {
+        return Err("No command given".to_string());
+    };
+    let mut options = Options::parse(args)?;
+
+    let output = match command.as_str() {
+        "eval" => {
+            let [cards] = options.positional::<1>()?;
+            let hand = best_hand(&cards)?;
+            format!(
+                "{}: {}\n{}\n",
+                hand.poker_hand_type().expect("best_of sorts the hand"),
+                describe(&hand),
+                cards_text(hand.cards())
+            )
+        }
+        "compare" => {
+            let [first, second] = options.positional::<2>()?;
+            encoding::mask(&[parse_cards(&first)?, parse_cards(&second)?].concat())?;
+            let first = best_hand(&first)?;
+            let second = best_hand(&second)?;
+            let winner = match first.cmp(&second) {
+                std::cmp::Ordering::Greater => "Hand 1 wins",
+                std::cmp::Ordering::Less => "Hand 2 wins",
+                std::cmp::Ordering::Equal => "Tie",
+            };
+            format!(
+                "Hand 1: {} ({})\nHand 2: {} ({})\n{winner}\n",
+                describe(&first),
+                cards_text(first.cards()),
+                describe(&second),
+                cards_text(second.cards())
+            )
+        }
+        "equity" => {
+            let board = match options.take("--board") {
+                Some(board) => parse_cards(&board)?,
+                None => Vec::new(),
+            };
+            let samples = options.number("--samples")?.unwrap_or(20_000);
+            let seed = options.number("--seed")?.unwrap_or_else(time_seed);
+            let hands = options
+                .rest()?
+                .iter()
+                .map(|hand| parse_cards(hand))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let result = equity(&hands, &board, samples, seed)?;
+            let mut output = String::new();
+            for (i, hand) in hands.iter().enumerate() {
+                let _ = writeln!(
+                    output,
+                    "{:<6} {:>6.2}%  wins {:>8}  ties {:>8}",
+                    cards_text(hand).replace(' ', ""),
+                    100.0 * result.equities[i],
+                    result.wins[i],
+                    result.ties[i]
+                );
+            }
+            if result.exact {
+                let _ = writeln!(output, "Exact over all {} boards", result.boards);
+            } else {
+                let _ = writeln!(output, "{} random boards, seed {seed}", result.boards);
+            }
+            output
+        }
+        "deal" => {
+            let players = options.number("--players")?.unwrap_or(2) as usize;
+            let seed = options.number("--seed")?.unwrap_or_else(time_seed);
+            options.rest_is_empty()?;
+            deal(players, seed)?
+        }
+        "preflop" => {
+            let path = options
+                .take("--table")
+                .ok_or_else(|| "preflop needs a --table file".to_string())?;
+            let [first, second] = options.positional::<2>()?;
+            let first: StartingHand = first.parse()?;
+            let second: StartingHand = second.parse()?;
+            let file = File::open(&path).map_err(|error| format!("{path}: {error}"))?;
+            let table = PreflopTable::read(BufReader::new(file))
+                .map_err(|error| format!("{path}: {error}"))?;
+            format!(
+                "{first} against {second}: {:.2}%\n",
+                100.0 * table.equity(first, second)
+            )
+        }
+        "preflop-table" => {
+            let samples = options.number("--samples")?.unwrap_or(2_000);
+            let seed = options.number("--seed")?.unwrap_or(0);
+            let mut runner = Runner::new(seed).chunk_size(1).on_progress(|progress| {
+                eprint!(
+                    "\r{}/{} matchups, {:.1} a second",
+                    progress.done,
+                    progress.total,
+                    progress.per_second()
+                );
+            });
+            if let Some(threads) = options.number("--threads")? {
+                runner = runner.threads(threads as usize);
+            }
+            let [path] = options.positional::<1>()?;
+
+            let table = PreflopTable::generate(samples, &runner)?;
+            eprintln!();
+            let file = File::create(&path).map_err(|error| format!("{path}: {error}"))?;
+            let mut out = BufWriter::new(file);
+            table
+                .write(&mut out)
+                .and_then(|()| out.flush())
+                .map_err(|error| format!("{path}: {error}"))?;
+            format!("Wrote {path}, {samples} boards a matchup, seed {seed}\n")
+        }
+        "ofc" => {
+            let hands = options
+                .rest()?
+                .iter()
+                .map(|hand| hand.parse())
+                .collect::<Result<Vec<OfcHand>, _>>()?;
+            if hands.len() < 2 {
+                return Err("Scoring needs at least two hands".to_string());
+            }
+            let dealt: Vec<Card> = hands
+                .iter()
+                .flat_map(|hand| [hand.top(), hand.middle().cards(), hand.bottom().cards()])
+                .flatten()
+                .copied()
+                .collect();
+            encoding::mask(&dealt)?;
+
+            let mut output = String::new();
+            for (i, (hand, points)) in hands.iter().zip(ofc::score(&hands)).enumerate() {
+                let royalties = hand.royalties();
+                let _ = write!(output, "Player {}  {points:+}  {hand}", i + 1);
+                if hand.is_foul() {
+                    let _ = write!(output, "  (foul)");
+                } else if royalties.total() > 0 {
+                    let _ = write!(
+                        output,
+                        "  (royalties {}: {} / {} / {})",
+                        royalties.total(),
+                        royalties.top,
+                        royalties.middle,
+                        royalties.bottom
+                    );
+                }
+                if hand.fantasyland() {
+                    let _ = write!(output, "  Fantasyland");
+                }
+                output.push('\n');
+            }
+            output
+        }
+        "repl" => {
+            let samples = options.number("--samples")?.unwrap_or(5_000);
+            let seed = options.number("--seed")?.unwrap_or_else(time_seed);
+            options.rest_is_empty()?;
+            repl::run(samples, seed).map_err(|error| error.to_string())?;
+            String::new()
+        }
+        "play" => {
+            let session = options
+                .take("--session")
+                .unwrap_or_else(|| "poker-session.txt".to_string());
+            let seed = options.number("--seed")?.unwrap_or_else(time_seed);
+            options.rest_is_empty()?;
+            play::run(std::path::Path::new(&session), seed)?;
+            String::new()
+        }
+        "help" | "--help" | "-h" => format!("{USAGE}\n"),
+        _ => return Err(format!("Unknown command {command:?}")),
+    };
+
+    Ok(output)
+}
+
+// Deals Hold'em the way the table does: hole cards one at a time around the table, and a burn
+// card before the flop, the turn and the river.
+fn deal(players: usize, seed: u64) -> Result<String, String> {
+    if !(2..=10).contains(&players) {
+        return Err(format!("Deal for 2 to 10 players, not {players}"));
+    }
+
+    let mut deck = Deck::shuffled(seed);
+    let mut deal_card = || deck.deal().ok_or("The deck ran out of cards");
+    let mut hole_cards = vec![Vec::new(); players];
+    for _ in 0..2 {
+        for cards in &mut hole_cards {
+            cards.push(deal_card()?);
+        }
+    }
+    let mut board = Vec::new();
+    for street in [3, 1, 1] {
+        deal_card()?;
+        for _ in 0..street {
+            board.push(deal_card()?);
+        }
+    }
+
+    let mut output = format!("Seed {seed}\nBoard: {}\n", cards_text(&board));
+    let mut best = Vec::new();
+    for (i, cards) in hole_cards.iter().enumerate() {
+        let mut all = cards.clone();
+        all.extend(&board);
+        let hand = PokerHand::best_of(&all)?;
+        let _ = writeln!(
+            output,
+            "Player {}: {}  {}",
+            i + 1,
+            cards_text(cards),
+            describe(&hand)
+        );
+        best.push(hand);
+    }
+
+    let top = best.iter().max().expect("At least two players");
+    let winners: Vec<String> = (0..players)
+        .filter(|&i| best[i] == *top)
+        .map(|i| format!("Player {}", i + 1))
+        .collect();
+    let _ = writeln!(output, "Winner: {}", winners.join(", "));
+    Ok(output)
+}
+
+fn best_hand(text: &str) -> Result<PokerHand, String> {
+    let cards = parse_cards(text)?;
+    if cards.len() > 7 {
+        return Err(format!("{text:?} has {} cards, at most 7 fit", cards.len()));
+    }
+    for (i, card) in cards.iter().enumerate() {
+        if cards[i + 1..].iter().any(|other| other.is_same_card(card)) {
+            return Err(format!("{card} shows up twice in {text:?}"));
+        }
+    }
+
+    Ok(PokerHand::best_of(&cards)?)
+}
+
+fn describe(hand: &PokerHand) -> String {
+    hand.description().expect("best_of sorts the hand")
+}
+
+fn cards_text(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(Card::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn time_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+// `--name value` pairs plus the arguments in between.
+struct Options {
+    named: Vec<(String, String)>,
+    positional: Vec<String>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Options {
+            named: Vec::new(),
+            positional: Vec::new(),
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg.starts_with("--") && arg.len() > 2 {
+                let value = args.next().ok_or_else(|| format!("{arg} needs a value"))?;
+                options.named.push((arg.clone(), value.clone()));
+            } else {
+                options.positional.push(arg.clone());
+            }
+        }
+
+        Ok(options)
+    }
+
+    fn take(&mut self, name: &str) -> Option<String> {
+        let i = self.named.iter().position(|(n, _)| n == name)?;
+        Some(self.named.remove(i).1)
+    }
+
+    fn number(&mut self, name: &str) -> Result<Option<u64>, String> {
+        self.take(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| format!("{name} needs a number, not {value:?}"))
+            })
+            .transpose()
+    }
+
+    fn rest_is_empty(&self) -> Result<(), String> {
+        if let Some((name, _)) = self.named.first() {
+            return Err(format!("Unknown option {name}"));
+        }
+        if let Some(arg) = self.positional.first() {
+            return Err(format!("Unexpected argument {arg:?}"));
+        }
+        Ok(())
+    }
+
+    // Whatever positional arguments are left, once every option was taken.
+    fn rest(&mut self) -> Result<Vec<String>, String> {
+        let positional = std::mem::take(&mut self.positional);
+        self.rest_is_empty()?;
+        Ok(positional)
+    }
+
+    fn positional<const N: usize>(&mut self) -> Result<[String; N], String> {
+        let rest = self.rest()?;
+        let count = rest.len();
+        rest.try_into()
+            .map_err(|_| format!("Expected {N} argument(s), got {count}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_args(args: &str) -> Result<String, String> {
+        // Cards are quoted on a real command line; here `|` separates the arguments.
+        let args: Vec<String> = args.split('|').map(|arg| arg.trim().to_string()).collect();
+        run(&args)
+    }
+
+    #[test]
+    fn cli_eval_and_compare() {
+        assert_eq!(
+            run_args("eval | Ah Kh Qh Jh Th").unwrap(),
+            "Royal flush: Royal flush\nAh Kh Qh Jh Th\n"
+        );
+        assert_eq!(
+            run_args("eval | 3c Kd 3h Ks Kc 2d 2s").unwrap(),
+            "Full house: Full house, Kings full of Threes\nKd Ks Kc 3c 3h\n"
+        );
+
+        let output = run_args("compare | 9c 9d 2h 5s Ac | Jc 4d Jh 4s Ad").unwrap();
+        assert!(output.ends_with("Hand 2 wins\n"), "{output}");
+        let output = run_args("compare | 9c 9d 2h 5s Ac | 9h 9s 2c 5d Ad").unwrap();
+        assert!(output.ends_with("Tie\n"), "{output}");
+
+        assert!(run_args("eval | Ah Kh").is_err());
+        assert!(run_args("eval | Ah Ah Qh Jh Th").is_err());
+        assert!(run_args("compare | Ah Kh Qh Jh Th").is_err());
+        assert_eq!(
+            run_args("compare | Ah Kh Qh Jh Th | As Ks Qs Js Ah").unwrap_err(),
+            "Ah shows up twice"
+        );
+        assert!(run_args("shuffle").is_err());
+    }
+
+    #[test]
+    fn cli_equity_and_deal() {
+        let output = run_args("equity | AhAd | KhKd | --board | 2c7s9dJc").unwrap();
+        assert_eq!(
+            output,
+            "AhAd    95.45%  wins       42  ties        0\n\
+             KhKd     4.55%  wins        2  ties        0\n\
+             Exact over all 44 boards\n"
+        );
+        assert!(run_args("equity | AhAd | --board | 2c7s9dJc").is_err());
+        assert!(run_args("equity | AhAd | KhKd | --samples | lots").is_err());
+
+        let output = run_args("deal | --players | 6 | --seed | 42").unwrap();
+        assert_eq!(
+            output,
+            run_args("deal | --seed | 42 | --players | 6").unwrap()
+        );
+        assert_eq!(
+            output
+                .lines()
+                .filter(|line| line.starts_with("Player"))
+                .count(),
+            6
+        );
+        assert!(output.starts_with("Seed 42\nBoard: "));
+        assert!(run_args("deal | --players | 1").is_err());
+        assert!(run_args("deal | --colour | red").is_err());
+    }
+
+    #[test]
+    fn cli_preflop_lookup() {
+        // A table where every hand has no equity against any later hand in the chart.
+        let path = std::env::temp_dir().join(format!("preflop-{}.bin", std::process::id()));
+        let mut bytes = b"PKPF\x01".to_vec();
+        bytes.extend([0; 16]);
+        bytes.extend(vec![0; 169 * 170]);
+        std::fs::write(&path, bytes).unwrap();
+        let path = path.to_str().unwrap().to_string();
+
+        assert_eq!(
+            run_args(&format!("preflop | AA | KK | --table | {path}")).unwrap(),
+            "AA against KK: 0.00%\n"
+        );
+        assert_eq!(
+            run_args(&format!("preflop | 72o | aks | --table | {path}")).unwrap(),
+            "72o against AKs: 100.00%\n"
+        );
+        assert!(run_args(&format!("preflop | AA | AK | --table | {path}")).is_err());
+        assert!(run_args("preflop | AA | KK").is_err());
+        assert!(run_args("preflop | AA | KK | --table | /no/such/table").is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn cli_ofc() {
+        let output = run_args(
+            "ofc | Qh Qd 5c / Ks Kd 4c 4h 2s / 7c 8c 9c Tc 3c \
+             | Ah Jh 2c / 9s 9h 3s 3h 8s / Jc Jd Js 5h 5d \
+             | Kh Kc 2d / Qs Qc 4d 6c 2h / 7d 8d 9d Td 3d",
+        )
+        .unwrap();
+        assert_eq!(
+            output,
+            "Player 1  +23  Qh Qd 5c / Ks Kd 4c 4h 2s / Tc 9c 8c 7c 3c  \
+             (royalties 11: 7 / 0 / 4)  Fantasyland\n\
+             Player 2  +6  Ah Jh 2c / 9s 9h 3s 3h 8s / Jc Jd Js 5h 5d  (royalties 6: 0 / 0 / 6)\n\
+             Player 3  -29  Kh Kc 2d / Qs Qc 6c 4d 2h / Td 9d 8d 7d 3d  (foul)\n"
+        );
+        assert!(run_args("ofc | Qh Qd 5c / Ks Kd 4c 4h 2s / 7c 8c 9c Tc 3c").is_err());
+        assert!(run_args("ofc | Qh Qd / Ks Kd 4c 4h 2s / 7c 8c 9c Tc 3c | x").is_err());
+        assert_eq!(
+            run_args(
+                "ofc | Qh Qd 5c / Ks Kd 4c 4h 2s / 7c 8c 9c Tc 3c \
+                 | Ah Kd 2c / 9s 9h 3s 3h 8s / Jc Jd Js 5h 5d"
+            ),
+            Err("Kd shows up twice".to_string())
+        );
+    }
+}
@@ -0,0 +1,503 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use book_examples::deck::Deck;
+use book_examples::equity::equity_against_random;
+use book_examples::holdem::{Action, HoldemConfig, HoldemGame, LegalAction};
+use book_examples::{Card, PokerHand, Suit};
+
+use crate::describe;
+
+const CONFIG: HoldemConfig = HoldemConfig {
+    small_blind: 1,
+    big_blind: 2,
+    ante: 0,
+};
+const STARTING_STACK: u32 = 200;
+const YOU: usize = 0;
+const BOT: usize = 1;
+const NAMES: [&str; 2] = ["You", "Bot"];
+// Random boards the bot deals itself to judge its hand. Enough to tell good from bad.
+const BOT_SAMPLES: u64 = 200;
+
+// Clears the terminal and moves the cursor to the top left.
+const CLEAR: &str = "\x1b[2J\x1b[H";
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+/// Stacks and button carried from one hand to the next, saved to a file after every hand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub hands: u64,
+    pub stacks: [u32; 2],
+    /// Seat that had the button in the last hand.
+    pub button: Option<usize>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session {
+            hands: 0,
+            stacks: [STARTING_STACK; 2],
+            button: None,
+        }
+    }
+}
+
+impl Session {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Session::default()),
+            Err(error) => return Err(format!("Unable to read {}: {error}", path.display())),
+        };
+
+        let mut session = Session::default();
+        for (i, line) in text.lines().enumerate() {
+            let error = || format!("{} line {}: {line:?}", path.display(), i + 1);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line.split_once(' ').ok_or_else(error)?;
+            match key {
+                "hands" => session.hands = value.parse().map_err(|_| error())?,
+                "button" => session.button = Some(value.parse().map_err(|_| error())?),
+                "stacks" => {
+                    let stacks: Vec<u32> = value
+                        .split_whitespace()
+                        .map(|stack| stack.parse().map_err(|_| error()))
+                        .collect::<Result<_, _>>()?;
+                    session.stacks = stacks.try_into().map_err(|_| error())?;
+                }
+                _ => return Err(error()),
+            }
+        }
+        if session.button.is_some_and(|button| button > BOT) {
+            return Err(format!(
+                "{}: the button must be on seat 0 or 1",
+                path.display()
+            ));
+        }
+
+        Ok(session)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut text = "# poker play session\n".to_string();
+        let _ = writeln!(text, "hands {}", self.hands);
+        if let Some(button) = self.button {
+            let _ = writeln!(text, "button {button}");
+        }
+        let _ = writeln!(text, "stacks {} {}", self.stacks[YOU], self.stacks[BOT]);
+
+        fs::write(path, text).map_err(|error| format!("Unable to save {}: {error}", path.display()))
+    }
+}
+
+/// What the player typed at the action prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    Act(Action),
+    Quit,
+}
+
+/// Reads `f`, `k` (check), `c` (call, or check when there is nothing to call), `b 20`,
+/// `r 40`, `a` (all in) and `q`, and the words they stand for.
+pub fn parse_input(line: &str, legal: &[LegalAction]) -> Result<Input, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let amount = |word: Option<&&str>| -> Result<u32, String> {
+        let word = word.ok_or("Say how much, e.g. `r 20`")?;
+        word.parse()
+            .map_err(|_| format!("{word:?} is not an amount"))
+    };
+    let allowed = |action: Action| {
+        let ok = legal.iter().any(|legal| match (legal, action) {
+            (LegalAction::Fold, Action::Fold) | (LegalAction::Check, Action::Check) => true,
+            (LegalAction::Call(_), Action::Call) => true,
+            (LegalAction::Bet { min, max }, Action::Bet(to))
+            | (LegalAction::Raise { min, max }, Action::Raise(to)) => (*min..=*max).contains(&to),
+            _ => false,
+        });
+        if ok {
+            Ok(Input::Act(action))
+        } else {
+            Err(format!("{action:?} is not allowed here"))
+        }
+    };
+    let bet_or_raise = |to: u32| {
+        if legal
+            .iter()
+            .any(|legal| matches!(legal, LegalAction::Bet { .. }))
+        {
+            allowed(Action::Bet(to))
+        } else {
+            allowed(Action::Raise(to))
+        }
+    };
+
+    match words.first().map(|word| word.to_lowercase()).as_deref() {
+        Some("q" | "quit") => Ok(Input::Quit),
+        Some("f" | "fold") => allowed(Action::Fold),
+        Some("k" | "check") => allowed(Action::Check),
+        Some("c" | "call") if legal.contains(&LegalAction::Check) => allowed(Action::Check),
+        Some("c" | "call") => allowed(Action::Call),
+        Some("b" | "bet" | "r" | "raise") => bet_or_raise(amount(words.get(1))?),
+        Some("a" | "allin") => match legal.iter().find_map(|legal| match legal {
+            LegalAction::Bet { max, .. } | LegalAction::Raise { max, .. } => Some(*max),
+            _ => None,
+        }) {
+            Some(max) => bet_or_raise(max),
+            None => allowed(Action::Call),
+        },
+        _ => Err("Type f, k, c, b <to>, r <to>, a or q".to_string()),
+    }
+}
+
+/// The bot's move: it estimates its chances against a random hand and bets its strong hands,
+/// calls when the price is right and otherwise gives up.
+pub fn bot_action(game: &HoldemGame, seed: u64) -> Action {
+    let state = game
+        .betting_state()
+        .expect("The bot only acts when it is its turn");
+    let legal = game.legal_actions();
+    let seat = game.to_act().expect("Someone is to act");
+    let strength = equity_against_random(
+        game.seats()[seat].hole_cards(),
+        game.board(),
+        1,
+        BOT_SAMPLES,
+        seed,
+    )
+    .map(|equity| equity.equities[0])
+    .unwrap_or(0.5);
+
+    let to_call = state.to_call();
+    let pot_odds = to_call as f64 / (state.pot + to_call) as f64;
+
+    if strength > 0.7 {
+        // Something around the size of the pot, kept inside what is allowed.
+        let target = state.current_bet + state.pot;
+        for action in &legal {
+            match *action {
+                LegalAction::Bet { min, max } => return Action::Bet(target.clamp(min, max)),
+                LegalAction::Raise { min, max } => return Action::Raise(target.clamp(min, max)),
+                _ => {}
+            }
+        }
+    }
+
+    if legal.contains(&LegalAction::Check) {
+        Action::Check
+    } else if strength > pot_odds + 0.05 || strength > 0.7 {
+        Action::Call
+    } else {
+        Action::Fold
+    }
+}
+
+pub fn describe_action(seat: usize, action: Action) -> String {
+    let verb = |you: &'static str, bot: &'static str| if seat == YOU { you } else { bot };
+    match action {
+        Action::Fold => format!("{} {}", NAMES[seat], verb("fold", "folds")),
+        Action::Check => format!("{} {}", NAMES[seat], verb("check", "checks")),
+        Action::Call => format!("{} {}", NAMES[seat], verb("call", "calls")),
+        Action::Bet(to) => format!("{} {} {to}", NAMES[seat], verb("bet", "bets")),
+        Action::Raise(to) => format!("{} {} to {to}", NAMES[seat], verb("raise", "raises")),
+    }
+}
+
+fn card_box(card: &Card) -> String {
+    let text = card.to_string();
+    let rank = &text[..1];
+    let (symbol, color) = match card.suit() {
+        Suit::Heart => ("♥", RED),
+        Suit::Diamond => ("♦", RED),
+        Suit::Club => ("♣", ""),
+        Suit::Spade => ("♠", ""),
+    };
+    if color.is_empty() {
+        format!("[{rank}{symbol}]")
+    } else {
+        format!("[{color}{rank}{symbol}{RESET}]")
+    }
+}
+
+fn cards_row(cards: &[Card], hidden: bool) -> String {
+    if hidden {
+        return vec!["[##]"; cards.len()].join(" ");
+    }
+    cards.iter().map(card_box).collect::<Vec<_>>().join(" ")
+}
+
+/// The table as text: the bot at the top, the board and pot in the middle, you at the bottom.
+/// The bot's cards stay face down unless `reveal` is set.
+pub fn render(game: &HoldemGame, session: &Session, log: &[String], reveal: bool) -> String {
+    let seats = game.seats();
+    let button = game.button();
+    let row = |seat: usize, hidden: bool| {
+        format!(
+            "{:<4}{:<3} chips {:>5}   bet {:>4}   {}{}\n",
+            NAMES[seat],
+            if button == Some(seat) { "(B)" } else { "" },
+            seats[seat].stack,
+            seats[seat].street_bet(),
+            cards_row(seats[seat].hole_cards(), hidden),
+            if seats[seat].is_folded() {
+                "  folded"
+            } else {
+                ""
+            }
+        )
+    };
+
+    let mut screen = format!(
+        "{CLEAR}Hand #{}   {:?}\n\n",
+        session.hands + 1,
+        game.street()
+    );
+    screen.push_str(&row(BOT, !reveal));
+    let _ = writeln!(
+        screen,
+        "\nBoard   {}\nPot     {}\n",
+        if game.board().is_empty() {
+            "-".to_string()
+        } else {
+            cards_row(game.board(), false)
+        },
+        game.pot()
+    );
+    screen.push_str(&row(YOU, false));
+    screen.push('\n');
+    for line in log.iter().rev().take(6).rev() {
+        let _ = writeln!(screen, "  {line}");
+    }
+
+    screen
+}
+
+fn prompt(legal: &[LegalAction]) -> String {
+    let options: Vec<String> = legal
+        .iter()
+        .map(|legal| match legal {
+            LegalAction::Fold => "(f)old".to_string(),
+            LegalAction::Check => "chec(k)".to_string(),
+            LegalAction::Call(amount) => format!("(c)all {amount}"),
+            LegalAction::Bet { min, max } => format!("(b)et {min}-{max}"),
+            LegalAction::Raise { min, max } => format!("(r)aise to {min}-{max}"),
+        })
+        .collect();
+    format!("\n{}, (a)ll in, (q)uit > ", options.join(", "))
+}
+
+/// Plays hands against the bot on stdin and stdout until you quit, saving to `path` after
+/// every hand. A hand that is quit half way is not counted.
+pub fn run(path: &Path, seed: u64) -> Result<(), String> {
+    let mut session = Session::load(path)?;
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut stdout = io::stdout();
+    let mut read_line = |text: &str| -> Result<Option<String>, String> {
+        print!("{text}");
+        stdout.flush().map_err(|error| error.to_string())?;
+        lines.next().transpose().map_err(|error| error.to_string())
+    };
+
+    loop {
+        if session.stacks.contains(&0) {
+            let winner = if session.stacks[YOU] == 0 {
+                "The bot wins"
+            } else {
+                "You win"
+            };
+            let Some(line) = read_line(&format!(
+                "{winner} the match. Enter for a new one, q to quit > "
+            ))?
+            else {
+                return Ok(());
+            };
+            if line.trim() == "q" {
+                return Ok(());
+            }
+            session = Session::default();
+            session.save(path)?;
+        }
+
+        let mut game = HoldemGame::new(&session.stacks, CONFIG)?;
+        let button = session.button.map_or(YOU, |button| 1 - button);
+        let hand_seed = seed.wrapping_add(session.hands);
+        game.start_hand_at(Deck::shuffled(hand_seed), button)?;
+
+        let mut log = vec![format!("{} posted the small blind", NAMES[button])];
+        let mut message = String::new();
+        while !game.is_hand_over() {
+            let seat = game
+                .to_act()
+                .expect("Someone is to act until the hand is over");
+            let action = if seat == BOT {
+                bot_action(&game, hand_seed.wrapping_add(log.len() as u64))
+            } else {
+                let legal = game.legal_actions();
+                let screen = render(&game, &session, &log, false);
+                let Some(line) = read_line(&format!("{screen}{message}{}", prompt(&legal)))? else {
+                    return Ok(());
+                };
+                match parse_input(&line, &legal) {
+                    Ok(Input::Act(action)) => action,
+                    Ok(Input::Quit) => return Ok(()),
+                    Err(error) => {
+                        message = format!("\n{error}");
+                        continue;
+                    }
+                }
+            };
+
+            message.clear();
+            game.act(action).map_err(|error| error.to_string())?;
+            log.push(describe_action(seat, action));
+        }
+
+        let showdown = !game.history().showdown.is_empty();
+        if showdown {
+            for seat in [YOU, BOT] {
+                let mut cards = game.seats()[seat].hole_cards().to_vec();
+                cards.extend(game.board());
+                let hand = PokerHand::best_of(&cards)?;
+                let verb = if seat == YOU { "show" } else { "shows" };
+                log.push(format!("{} {verb} {}", NAMES[seat], describe(&hand)));
+            }
+        }
+        for seat in [YOU, BOT] {
+            let won = game.winnings()[seat];
+            if won > 0 {
+                log.push(format!(
+                    "{} {} {won}",
+                    NAMES[seat],
+                    if seat == YOU { "win" } else { "wins" }
+                ));
+            }
+        }
+
+        let screen = render(&game, &session, &log, showdown);
+        session.hands += 1;
+        session.button = Some(button);
+        session.stacks = [game.seats()[YOU].stack, game.seats()[BOT].stack];
+        session.save(path)?;
+
+        let Some(line) = read_line(&format!("{screen}\nEnter for the next hand, q to quit > "))?
+        else {
+            return Ok(());
+        };
+        if line.trim() == "q" {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn play_parse_input() {
+        let facing_bet = [
+            LegalAction::Fold,
+            LegalAction::Call(4),
+            LegalAction::Raise { min: 10, max: 200 },
+        ];
+        assert_eq!(parse_input("f", &facing_bet), Ok(Input::Act(Action::Fold)));
+        assert_eq!(parse_input("c", &facing_bet), Ok(Input::Act(Action::Call)));
+        assert_eq!(
+            parse_input("r 20", &facing_bet),
+            Ok(Input::Act(Action::Raise(20)))
+        );
+        assert_eq!(
+            parse_input("b 20", &facing_bet),
+            Ok(Input::Act(Action::Raise(20)))
+        );
+        assert_eq!(
+            parse_input("a", &facing_bet),
+            Ok(Input::Act(Action::Raise(200)))
+        );
+        assert_eq!(parse_input("Q", &facing_bet), Ok(Input::Quit));
+        assert!(parse_input("k", &facing_bet).is_err());
+        assert!(parse_input("r 5", &facing_bet).is_err());
+        assert!(parse_input("r", &facing_bet).is_err());
+        assert!(parse_input("dance", &facing_bet).is_err());
+
+        let checked_to = [LegalAction::Check, LegalAction::Bet { min: 2, max: 50 }];
+        assert_eq!(parse_input("c", &checked_to), Ok(Input::Act(Action::Check)));
+        assert_eq!(
+            parse_input("r 8", &checked_to),
+            Ok(Input::Act(Action::Bet(8)))
+        );
+        assert!(parse_input("f", &checked_to).is_err());
+
+        let all_in_call = [LegalAction::Fold, LegalAction::Call(30)];
+        assert_eq!(parse_input("a", &all_in_call), Ok(Input::Act(Action::Call)));
+    }
+
+    #[test]
+    fn play_bot_only_makes_legal_moves() {
+        for seed in 0..8 {
+            let mut game = HoldemGame::new(&[200, 200], CONFIG).unwrap();
+            game.start_hand(Deck::shuffled(seed)).unwrap();
+
+            while !game.is_hand_over() {
+                // Both seats are played by the bot here.
+                let action = bot_action(&game, seed);
+                game.act(action).unwrap();
+            }
+            let stacks: u32 = game.seats().iter().map(|seat| seat.stack).sum();
+            assert_eq!(stacks, 400);
+        }
+    }
+
+    #[test]
+    fn play_render_hides_the_bot() {
+        let mut game = HoldemGame::new(&[200, 200], CONFIG).unwrap();
+        game.start_hand(Deck::shuffled(3)).unwrap();
+        let log = vec![
+            describe_action(BOT, Action::Raise(6)),
+            describe_action(YOU, Action::Call),
+        ];
+        assert_eq!(log, ["Bot raises to 6", "You call"]);
+
+        let screen = render(&game, &Session::default(), &log, false);
+        assert!(screen.starts_with(CLEAR));
+        assert!(screen.contains("Hand #1   Preflop"));
+        assert!(
+            screen.contains("Bot     chips   198   bet    2   [##] [##]"),
+            "{screen}"
+        );
+        assert!(screen.contains("You (B) chips   199   bet    1   "));
+        assert!(screen.contains("  Bot raises to 6\n  You call\n"));
+
+        let revealed = render(&game, &Session::default(), &log, true);
+        assert!(!revealed.contains("[##]"));
+    }
+
+    #[test]
+    fn play_session_round_trip() {
+        let path = std::env::temp_dir().join(format!("poker-session-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        assert_eq!(Session::load(&path).unwrap(), Session::default());
+
+        let session = Session {
+            hands: 12,
+            stacks: [150, 250],
+            button: Some(1),
+        };
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path).unwrap(), session);
+
+        fs::write(&path, "hands 3\nstacks 100\n").unwrap();
+        assert!(Session::load(&path).is_err());
+        fs::write(&path, "button 4\n").unwrap();
+        assert!(Session::load(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,445 @@
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write};
+
+use book_examples::deck::Deck;
+use book_examples::equity::{Equity, equity, equity_against_random};
+use book_examples::outs::outs;
+use book_examples::partial::PartialHand;
+use book_examples::{Card, PokerHand, parse_cards};
+
+use crate::{cards_text, describe};
+
+const HELP: &str = "\
+Type cards to deal them: the first two are your hand, the next five the board.
+  hand <cards>      Sets your two cards
+  board <cards>     Sets the board, from no cards up to five
+  vs <cards>        Plays against these two cards instead of a random hand
+  vs random         Plays against a random hand again
+  best              Your best hand so far
+  outs              Cards that improve your hand, or put you ahead of the opponent
+  equity            Your chances against the opponent
+  undo              Takes back the last card
+  reset             Starts over
+  history           Commands typed this session; !N runs number N again, !! the last one
+  quit              Leaves";
+
+/// Line by line hand explorer. `handle` takes one line and gives back what to print, so the
+/// loop around it is the only part that touches stdin and stdout.
+pub struct Repl {
+    hand: Vec<Card>,
+    board: Vec<Card>,
+    villain: Option<Vec<Card>>,
+    history: Vec<String>,
+    samples: u64,
+    seed: u64,
+}
+
+impl Repl {
+    pub fn new(samples: u64, seed: u64) -> Self {
+        Repl {
+            hand: Vec::new(),
+            board: Vec::new(),
+            villain: None,
+            history: Vec::new(),
+            samples,
+            seed,
+        }
+    }
+
+    /// What to print for `line`, or `None` when the session is over.
+    pub fn handle(&mut self, line: &str) -> Option<Result<String, String>> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Some(Ok(String::new()));
+        }
+
+        let line = match self.recall(line) {
+            Ok(line) => line,
+            Err(error) => return Some(Err(error)),
+        };
+        self.history.push(line.clone());
+
+        let (command, rest) = line.split_once(' ').unwrap_or((&line, ""));
+        let rest = rest.trim();
+        let result = match command {
+            "quit" | "exit" => return None,
+            "help" => Ok(HELP.to_string()),
+            "hand" => parse_cards(rest).and_then(|cards| self.set_hand(cards)),
+            "board" => parse_cards(rest).and_then(|cards| self.set_board(cards)),
+            "vs" if rest == "random" => {
+                self.villain = None;
+                Ok(self.status())
+            }
+            "vs" => parse_cards(rest).and_then(|cards| self.set_villain(cards)),
+            "best" => self.best(),
+            "outs" => self.outs(),
+            "equity" => self.equity(),
+            "undo" => self.undo(),
+            "reset" => {
+                *self = Repl {
+                    history: std::mem::take(&mut self.history),
+                    ..Repl::new(self.samples, self.seed)
+                };
+                Ok("Starting over.".to_string())
+            }
+            "history" => Ok(self
+                .history
+                .iter()
+                .enumerate()
+                .map(|(i, line)| format!("{:>3}  {line}", i + 1))
+                .collect::<Vec<_>>()
+                .join("\n")),
+            _ => match parse_cards(&line) {
+                Ok(cards) if !cards.is_empty() => self.add_cards(cards),
+                _ => Err(format!("Unknown command {command:?}, try `help`")),
+            },
+        };
+
+        Some(result)
+    }
+
+    // Expands `!!` and `!N` into the command they point at.
+    fn recall(&self, line: &str) -> Result<String, String> {
+        let Some(which) = line.strip_prefix('!') else {
+            return Ok(line.to_string());
+        };
+
+        let index = if which == "!" {
+            self.history.len().checked_sub(1)
+        } else {
+            which
+                .parse::<usize>()
+                .ok()
+                .and_then(|number| number.checked_sub(1))
+        };
+        index
+            .and_then(|index| self.history.get(index))
+            .cloned()
+            .ok_or_else(|| format!("Nothing in the history for {line:?}"))
+    }
+
+    fn set_hand(&mut self, cards: Vec<Card>) -> Result<String, String> {
+        if cards.len() != 2 {
+            return Err(format!("A hand has 2 cards, not {}", cards.len()));
+        }
+        self.check_unused(&cards, &[&self.board, self.villain_cards()])?;
+        self.hand = cards;
+        Ok(self.status())
+    }
+
+    fn set_board(&mut self, cards: Vec<Card>) -> Result<String, String> {
+        if cards.len() > 5 {
+            return Err(format!(
+                "The board has at most 5 cards, not {}",
+                cards.len()
+            ));
+        }
+        self.check_unused(&cards, &[&self.hand, self.villain_cards()])?;
+        self.board = cards;
+        Ok(self.status())
+    }
+
+    fn set_villain(&mut self, cards: Vec<Card>) -> Result<String, String> {
+        if cards.len() != 2 {
+            return Err(format!("A hand has 2 cards, not {}", cards.len()));
+        }
+        self.check_unused(&cards, &[&self.hand, &self.board])?;
+        self.villain = Some(cards);
+        Ok(self.status())
+    }
+
+    fn add_cards(&mut self, cards: Vec<Card>) -> Result<String, String> {
+        if self.hand.len() + self.board.len() + cards.len() > 7 {
+            return Err("Your hand and the board are full".to_string());
+        }
+        self.check_unused(&cards, &[&self.hand, &self.board, self.villain_cards()])?;
+
+        for card in cards {
+            if self.hand.len() < 2 {
+                self.hand.push(card);
+            } else {
+                self.board.push(card);
+            }
+        }
+        Ok(self.status())
+    }
+
+    fn undo(&mut self) -> Result<String, String> {
+        if self.board.pop().is_none() && self.hand.pop().is_none() {
+            return Err("There are no cards to take back".to_string());
+        }
+        Ok(self.status())
+    }
+
+    fn villain_cards(&self) -> &[Card] {
+        self.villain.as_deref().unwrap_or_default()
+    }
+
+    fn check_unused(&self, cards: &[Card], used: &[&[Card]]) -> Result<(), String> {
+        for (i, card) in cards.iter().enumerate() {
+            let taken = cards[..i]
+                .iter()
+                .chain(used.iter().copied().flatten())
+                .any(|other| other.is_same_card(card));
+            if taken {
+                return Err(format!("{card} is already dealt"));
+            }
+        }
+        Ok(())
+    }
+
+    // The cards, the best hand and the equity, printed after every change to the cards.
+    fn status(&self) -> String {
+        let mut status = format!(
+            "Hand: {}  Board: {}",
+            or_dash(&self.hand),
+            or_dash(&self.board)
+        );
+        if let Some(villain) = &self.villain {
+            let _ = write!(status, "  Against: {}", cards_text(villain));
+        }
+        if let Ok(best) = self.best() {
+            let _ = write!(status, "\n{best}");
+        } else if !self.hand.is_empty() {
+            let mut cards = self.hand.clone();
+            cards.extend(&self.board);
+            if let Ok(partial) = PartialHand::classify(&cards) {
+                let _ = write!(status, "\nSo far: {partial}");
+            }
+        }
+        if let Ok(equity) = self.equity() {
+            let _ = write!(status, "\n{equity}");
+        }
+        status
+    }
+
+    fn best(&self) -> Result<String, String> {
+        let hand = self.best_hand(&self.hand)?;
+        Ok(format!(
+            "Best hand: {} ({})",
+            describe(&hand),
+            cards_text(hand.cards())
+        ))
+    }
+
+    fn best_hand(&self, hole_cards: &[Card]) -> Result<PokerHand, String> {
+        let mut cards = hole_cards.to_vec();
+        cards.extend(&self.board);
+        if cards.len() < 5 {
+            return Err("Deal at least five cards to make a hand".to_string());
+        }
+        Ok(PokerHand::best_of(&cards)?)
+    }
+
+    fn equity(&self) -> Result<String, String> {
+        if self.hand.len() != 2 {
+            return Err("Deal your two cards first".to_string());
+        }
+
+        let (result, against): (Equity, &str) = match &self.villain {
+            Some(villain) => (
+                equity(
+                    &[self.hand.clone(), villain.clone()],
+                    &self.board,
+                    self.samples,
+                    self.seed,
+                )?,
+                "the opponent",
+            ),
+            None => (
+                equity_against_random(&self.hand, &self.board, 1, self.samples, self.seed)?,
+                "a random hand",
+            ),
+        };
+
+        Ok(format!(
+            "Equity against {against}: {:.1}% ({} {} boards)",
+            100.0 * result.equities[0],
+            result.boards,
+            if result.exact { "possible" } else { "random" }
+        ))
+    }
+
+    fn outs(&self) -> Result<String, String> {
+        if self.hand.len() != 2 || !(3..=4).contains(&self.board.len()) {
+            return Err("Outs need your hand and a flop or turn".to_string());
+        }
+
+        let current = self.best_hand(&self.hand)?;
+        let mut output = String::new();
+        if let Some(villain) = &self.villain
+            && current > self.best_hand(villain)?
+        {
+            let mut deck = Deck::new();
+            deck.remove(&self.hand);
+            deck.remove(&self.board);
+            deck.remove(villain);
+            let mut cards = Vec::new();
+            for &card in deck.cards() {
+                let mut board = self.board.clone();
+                board.push(card);
+                let mut ours = self.hand.clone();
+                ours.extend(&board);
+                let mut theirs = villain.clone();
+                theirs.extend(&board);
+                if PokerHand::best_of(&ours)? <= PokerHand::best_of(&theirs)? {
+                    cards.push(card);
+                }
+            }
+            let _ = write!(
+                output,
+                "You are ahead. Cards that put you behind or split ({}): {}",
+                cards.len(),
+                or_dash(&cards)
+            );
+            return Ok(output);
+        }
+
+        let outs = outs(&self.hand, &self.board, self.villain.as_deref())?;
+        let _ = write!(
+            output,
+            "{} ({}, {:.1}% by the river)",
+            match self.villain {
+                Some(_) => "Outs to get ahead".to_string(),
+                None => format!("Outs to improve on {}", describe(&current)),
+            },
+            outs.count(),
+            100.0 * outs.probability
+        );
+        for group in &outs.groups {
+            let _ = write!(
+                output,
+                "\n  {} ({:.1}%): {}",
+                group.draw,
+                100.0 * group.probability,
+                cards_text(&group.cards)
+            );
+        }
+
+        Ok(output)
+    }
+}
+
+fn or_dash(cards: &[Card]) -> String {
+    if cards.is_empty() {
+        "-".to_string()
+    } else {
+        cards_text(cards)
+    }
+}
+
+/// Runs the REPL on stdin and stdout until `quit` or the end of the input.
+pub fn run(samples: u64, seed: u64) -> io::Result<()> {
+    let mut repl = Repl::new(samples, seed);
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    writeln!(stdout, "Type `help` for the commands.")?;
+
+    let mut lines = stdin.lock().lines();
+    loop {
+        write!(stdout, "> ")?;
+        stdout.flush()?;
+        let Some(line) = lines.next().transpose()? else {
+            return Ok(());
+        };
+
+        match repl.handle(&line) {
+            None => return Ok(()),
+            Some(Ok(output)) if output.is_empty() => {}
+            Some(Ok(output)) => writeln!(stdout, "{output}")?,
+            Some(Err(error)) => writeln!(stdout, "error: {error}")?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handle(repl: &mut Repl, line: &str) -> Result<String, String> {
+        repl.handle(line).expect("Only quit ends the session")
+    }
+
+    #[test]
+    fn repl_builds_a_hand() {
+        let mut repl = Repl::new(1000, 1);
+        let output = handle(&mut repl, "Ah Kh").unwrap();
+        assert!(
+            output.starts_with("Hand: Ah Kh  Board: -\nSo far: High card Ace\n"),
+            "{output}"
+        );
+        assert!(
+            output.contains("Equity against a random hand: "),
+            "{output}"
+        );
+
+        let output = handle(&mut repl, "Qh 7c 2h").unwrap();
+        assert!(output.contains("Board: Qh 7c 2h"), "{output}");
+        assert!(output.contains("Best hand: High card Ace"), "{output}");
+
+        let output = handle(&mut repl, "vs 7d 7s").unwrap();
+        assert!(output.contains("Against: 7d 7s"), "{output}");
+        assert!(
+            output.contains("Equity against the opponent: ")
+                && output.contains("(990 possible boards)"),
+            "{output}"
+        );
+
+        let output = handle(&mut repl, "outs").unwrap();
+        // Any heart but the one that gives the opponent quads, or runner-runner broadway.
+        assert_eq!(
+            output,
+            "Outs to get ahead (8, 33.6% by the river)\n  \
+             Flush draw (32.7%): 3h 4h 5h 6h 8h 9h Th Jh\n  \
+             Backdoor straight (0.9%): Tc Jc Td Jd Ts Js"
+        );
+        let output = handle(&mut repl, "vs random").unwrap();
+        assert!(!output.contains("Against"), "{output}");
+
+        let output = handle(&mut repl, "outs").unwrap();
+        assert!(
+            output.starts_with("Outs to improve on High card Ace ("),
+            "{output}"
+        );
+        assert!(output.contains("\n  Pairing ("), "{output}");
+        assert!(output.contains("\n  Flush draw ("), "{output}");
+
+        let output = handle(&mut repl, "undo").unwrap();
+        assert!(
+            output.contains("Board: Qh 7c\nSo far: High card Ace\n"),
+            "{output}"
+        );
+        assert_eq!(
+            handle(&mut repl, "best").unwrap_err(),
+            "Deal at least five cards to make a hand"
+        );
+
+        assert!(handle(&mut repl, "Qh").is_err(), "Already dealt");
+        assert!(handle(&mut repl, "board 2c 3c 4c 5c 6c 7c").is_err());
+        assert!(
+            handle(&mut repl, "Jh Th 9h 8h").is_err(),
+            "Only three more cards fit"
+        );
+        assert!(handle(&mut repl, "frobnicate").is_err());
+        assert_eq!(handle(&mut repl, "reset").unwrap(), "Starting over.");
+        assert!(handle(&mut repl, "equity").is_err());
+        assert!(repl.handle("quit").is_none());
+    }
+
+    #[test]
+    fn repl_history() {
+        let mut repl = Repl::new(100, 1);
+        handle(&mut repl, "hand As Ad").unwrap();
+        handle(&mut repl, "board Ac 2d 7s").unwrap();
+        assert!(handle(&mut repl, "!!").unwrap().contains("Board: Ac 2d 7s"));
+        let output = handle(&mut repl, "!1").unwrap();
+        assert!(output.contains("Three of a kind, Aces"), "{output}");
+        assert!(handle(&mut repl, "!9").is_err());
+
+        assert_eq!(
+            handle(&mut repl, "history").unwrap(),
+            "  1  hand As Ad\n  2  board Ac 2d 7s\n  3  board Ac 2d 7s\n  4  hand As Ad\n  5  history"
+        );
+        assert_eq!(handle(&mut repl, "").unwrap(), "");
+    }
+}
@@ -0,0 +1,144 @@
+use crate::{Card, Suit};
+
+pub const SUITS: [Suit; 4] = [Suit::Club, Suit::Diamond, Suit::Heart, Suit::Spade];
+
+/// Small seeded random number generator (SplitMix64). The same seed always gives the same
+/// shuffles, which is what simulations and tests want.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform number in `0..n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "Rng::below needs a non empty range");
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Deck {
+    cards: Vec<Card>,
+}
+
+impl Default for Deck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deck {
+    /// A full, unshuffled 52 card deck.
+    pub fn new() -> Self {
+        let mut cards = Vec::with_capacity(52);
+        for suit in SUITS {
+            for rank in 2..=14 {
+                cards.push(Card { rank, suit });
+            }
+        }
+
+        Deck { cards }
+    }
+
+    pub fn shuffled(seed: u64) -> Self {
+        let mut deck = Deck::new();
+        deck.shuffle(&mut Rng::new(seed));
+        deck
+    }
+
+    /// Stacks the deck so that `cards` come out first, in order. Handy for tests and replays.
+    pub fn stacked(cards: &[Card]) -> Self {
+        let mut deck = Deck::new();
+        deck.remove(cards);
+        deck.cards.extend(cards.iter().rev());
+        deck
+    }
+
+    pub fn shuffle(&mut self, rng: &mut Rng) {
+        // Fisher-Yates
+        for i in (1..self.cards.len()).rev() {
+            let j = rng.below(i + 1);
+            self.cards.swap(i, j);
+        }
+    }
+
+    pub fn deal(&mut self) -> Option<Card> {
+        self.cards.pop()
+    }
+
+    /// Takes the given cards out of the deck, e.g. the hole cards and board we already know.
+    pub fn remove(&mut self, cards: &[Card]) {
+        self.cards
+            .retain(|card| !cards.iter().any(|other| other.is_same_card(card)));
+    }
+
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deck_shuffle() {
+        let deck = Deck::new();
+        assert_eq!(deck.len(), 52);
+
+        let shuffled = Deck::shuffled(42);
+        assert_eq!(shuffled.len(), 52);
+        for card in deck.cards() {
+            assert!(
+                shuffled
+                    .cards()
+                    .iter()
+                    .any(|other| other.rank == card.rank && other.suit == card.suit),
+                "Missing {card:?}"
+            );
+        }
+
+        let same_seed = Deck::shuffled(42);
+        assert!(
+            shuffled
+                .cards()
+                .iter()
+                .zip(same_seed.cards())
+                .all(|(a, b)| a.rank == b.rank && a.suit == b.suit)
+        );
+    }
+
+    #[test]
+    fn deck_stacked() {
+        let ace = Card::new(14, Suit::Spade).unwrap();
+        let two = Card::new(2, Suit::Club).unwrap();
+        let mut deck = Deck::stacked(&[ace, two]);
+
+        assert_eq!(deck.len(), 52);
+        let first = deck.deal().unwrap();
+        let second = deck.deal().unwrap();
+        assert_eq!((first.rank, first.suit), (14, Suit::Spade));
+        assert_eq!((second.rank, second.suit), (2, Suit::Club));
+    }
+}
@@ -0,0 +1,458 @@
+use std::io::{self, Read, Write};
+
+use crate::Card;
+use crate::deck::SUITS;
+
+/// Most cards a set can have for `combination_index`. C(52, 7) still fits in a `u32`.
+pub const MAX_INDEXED_CARDS: usize = 7;
+
+/// Number of the card from 0 to 51, which fits in 6 bits: clubs 2 - A are 0 - 12, then
+/// diamonds, hearts and spades.
+pub fn card_to_bits(card: Card) -> u8 {
+    let suit = SUITS
+        .iter()
+        .position(|suit| *suit == card.suit)
+        .expect("SUITS holds every suit") as u8;
+    suit * 13 + card.rank - 2
+}
+
+pub fn card_from_bits(bits: u8) -> Result<Card, String> {
+    if bits >= 52 {
+        return Err(format!("{bits} is not a card number, they go from 0 to 51"));
+    }
+
+    Ok(Card {
+        rank: bits % 13 + 2,
+        suit: SUITS[(bits / 13) as usize],
+    })
+}
+
+/// Packs up to ten cards into 6 bits each, the first card in the highest bits. Keeps the order
+/// of the cards, unlike `mask` and `combination_index`.
+pub fn pack(cards: &[Card]) -> Result<u64, String> {
+    if cards.len() > 10 {
+        return Err(format!("Only 10 cards fit in 64 bits, not {}", cards.len()));
+    }
+
+    Ok(cards
+        .iter()
+        .fold(0, |bits, card| bits << 6 | card_to_bits(*card) as u64))
+}
+
+pub fn unpack(bits: u64, count: usize) -> Result<Vec<Card>, String> {
+    if count > 10 || (count < 10 && bits >> (6 * count) != 0) {
+        return Err(format!("{bits:#x} does not hold {count} packed cards"));
+    }
+
+    (0..count)
+        .rev()
+        .map(|i| card_from_bits((bits >> (6 * i) & 0x3f) as u8))
+        .collect()
+}
+
+/// One bit per card, bit `card_to_bits(card)` being set for each card. Fails on duplicates.
+pub fn mask(cards: &[Card]) -> Result<u64, String> {
+    let mut mask = 0;
+    for card in cards {
+        let bit = 1 << card_to_bits(*card);
+        if mask & bit != 0 {
+            return Err(format!("{card} shows up twice"));
+        }
+        mask |= bit;
+    }
+
+    Ok(mask)
+}
+
+/// The cards of a mask, in the order of their numbers.
+pub fn from_mask(mask: u64) -> Result<Vec<Card>, String> {
+    if mask >> 52 != 0 {
+        return Err(format!("{mask:#x} has bits set above the 52 cards"));
+    }
+
+    (0..52)
+        .filter(|bit| mask & (1 << bit) != 0)
+        .map(card_from_bits)
+        .collect()
+}
+
+/// Position of the set of cards among all sets of that size, from 0 to C(52, n) - 1 (the
+/// colexicographic rank). The order of `cards` does not matter.
+pub fn combination_index(cards: &[Card]) -> Result<u32, String> {
+    if cards.is_empty() || cards.len() > MAX_INDEXED_CARDS {
+        return Err(format!(
+            "Can only index sets of 1 to {MAX_INDEXED_CARDS} cards, not {}",
+            cards.len()
+        ));
+    }
+
+    let mut bits: Vec<u8> = cards.iter().map(|card| card_to_bits(*card)).collect();
+    bits.sort_unstable();
+    if let Some(pair) = bits.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(format!("{} shows up twice", card_from_bits(pair[0])?));
+    }
+
+    Ok(bits
+        .iter()
+        .enumerate()
+        .map(|(i, &bits)| binomial(bits as u32, i as u32 + 1))
+        .sum::<u64>() as u32)
+}
+
+/// The set of `count` cards at `index`, sorted by card number. Undoes `combination_index`.
+pub fn from_combination_index(index: u32, count: usize) -> Result<Vec<Card>, String> {
+    if count == 0 || count > MAX_INDEXED_CARDS {
+        return Err(format!(
+            "Can only index sets of 1 to {MAX_INDEXED_CARDS} cards, not {count}"
+        ));
+    }
+    if index as u64 >= binomial(52, count as u32) {
+        return Err(format!("{index} is past the last set of {count} cards"));
+    }
+
+    let mut rest = index as u64;
+    let mut cards = Vec::with_capacity(count);
+    let mut below = 52;
+    for k in (1..=count as u32).rev() {
+        // The largest card number whose sets of `k` still fit in what is left of the index.
+        below = (k - 1..below)
+            .rev()
+            .find(|&n| binomial(n, k) <= rest)
+            .expect("C(k - 1, k) is 0");
+        rest -= binomial(below, k);
+        cards.push(card_from_bits(below as u8)?);
+    }
+
+    cards.reverse();
+    Ok(cards)
+}
+
+pub(crate) fn binomial(n: u32, k: u32) -> u64 {
+    if k > n {
+        return 0;
+    }
+    (0..k as u64).fold(1, |result, i| result * (n as u64 - i) / (i + 1))
+}
+
+/// How the hands of a `HandWriter` file are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandEncoding {
+    /// 6 bits per card, keeping the order of the cards.
+    Packed,
+    /// 52 bit mask in 7 bytes. Order is lost.
+    Mask,
+    /// `combination_index` in 4 bytes, the smallest. Order is lost.
+    Index,
+}
+
+impl HandEncoding {
+    fn record_len(self, cards: usize) -> usize {
+        match self {
+            HandEncoding::Packed => (6 * cards).div_ceil(8),
+            HandEncoding::Mask => 7,
+            HandEncoding::Index => 4,
+        }
+    }
+
+    fn code(self) -> u8 {
+        match self {
+            HandEncoding::Packed => 0,
+            HandEncoding::Mask => 1,
+            HandEncoding::Index => 2,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(HandEncoding::Packed),
+            1 => Some(HandEncoding::Mask),
+            2 => Some(HandEncoding::Index),
+            _ => None,
+        }
+    }
+}
+
+const MAGIC: &[u8; 4] = b"PKHS";
+
+/// Writes hands of the same size as fixed size records after a 6 byte header: `PKHS`, the
+/// encoding and the number of cards per hand.
+#[derive(Debug)]
+pub struct HandWriter<W: Write> {
+    inner: W,
+    encoding: HandEncoding,
+    cards: usize,
+}
+
+impl<W: Write> HandWriter<W> {
+    pub fn new(mut inner: W, encoding: HandEncoding, cards: usize) -> io::Result<Self> {
+        if cards == 0 || cards > MAX_INDEXED_CARDS {
+            return Err(invalid_input(format!(
+                "Hands must have 1 to {MAX_INDEXED_CARDS} cards, not {cards}"
+            )));
+        }
+
+        inner.write_all(MAGIC)?;
+        inner.write_all(&[encoding.code(), cards as u8])?;
+        Ok(HandWriter {
+            inner,
+            encoding,
+            cards,
+        })
+    }
+
+    pub fn write_hand(&mut self, hand: &[Card]) -> io::Result<()> {
+        if hand.len() != self.cards {
+            return Err(invalid_input(format!(
+                "This file holds hands of {} cards, not {}",
+                self.cards,
+                hand.len()
+            )));
+        }
+
+        let len = self.encoding.record_len(self.cards);
+        let record = match self.encoding {
+            // Left aligned, so the first card is in the high bits of the first byte.
+            HandEncoding::Packed => pack(hand).map(|bits| bits << (8 * len - 6 * self.cards)),
+            HandEncoding::Mask => mask(hand),
+            HandEncoding::Index => combination_index(hand).map(u64::from),
+        }
+        .map_err(invalid_input)?;
+
+        self.inner.write_all(&record.to_be_bytes()[8 - len..])
+    }
+
+    /// Flushes and hands back the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+/// Reads the hands of a file written by `HandWriter`, one at a time.
+#[derive(Debug)]
+pub struct HandReader<R: Read> {
+    inner: R,
+    encoding: HandEncoding,
+    cards: usize,
+}
+
+impl<R: Read> HandReader<R> {
+    pub fn new(mut inner: R) -> io::Result<Self> {
+        let mut header = [0; 6];
+        inner.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(invalid_data("Not a file of hands".to_string()));
+        }
+        let encoding = HandEncoding::from_code(header[4])
+            .ok_or_else(|| invalid_data(format!("Unknown hand encoding {}", header[4])))?;
+        let cards = header[5] as usize;
+        if cards == 0 || cards > MAX_INDEXED_CARDS {
+            return Err(invalid_data(format!(
+                "Hands of {cards} cards are not supported"
+            )));
+        }
+
+        Ok(HandReader {
+            inner,
+            encoding,
+            cards,
+        })
+    }
+
+    pub fn encoding(&self) -> HandEncoding {
+        self.encoding
+    }
+
+    pub fn cards_per_hand(&self) -> usize {
+        self.cards
+    }
+
+    /// The next hand, or `None` at the end of the file.
+    pub fn read_hand(&mut self) -> io::Result<Option<Vec<Card>>> {
+        let len = self.encoding.record_len(self.cards);
+        let mut bytes = [0; 8];
+        let record = &mut bytes[8 - len..];
+
+        let mut read = 0;
+        while read < len {
+            match self.inner.read(&mut record[read..]) {
+                Ok(0) if read == 0 => return Ok(None),
+                Ok(0) => return Err(invalid_data("The last hand is cut off".to_string())),
+                Ok(n) => read += n,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        let record = u64::from_be_bytes(bytes);
+        let hand = match self.encoding {
+            HandEncoding::Packed => unpack(record >> (8 * len - 6 * self.cards), self.cards),
+            HandEncoding::Mask => from_mask(record).and_then(|hand| {
+                if hand.len() == self.cards {
+                    Ok(hand)
+                } else {
+                    Err(format!("Mask {record:#x} holds {} cards", hand.len()))
+                }
+            }),
+            HandEncoding::Index => from_combination_index(record as u32, self.cards),
+        }
+        .map_err(invalid_data)?;
+
+        Ok(Some(hand))
+    }
+}
+
+impl<R: Read> Iterator for HandReader<R> {
+    type Item = io::Result<Vec<Card>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_hand().transpose()
+    }
+}
+
+pub(crate) fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+pub(crate) fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::{Deck, Rng};
+    use crate::parse_cards;
+
+    fn same_cards(a: &[Card], b: &[Card]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.is_same_card(b))
+    }
+
+    #[test]
+    fn encoding_cards() {
+        let deck = Deck::new();
+        for (i, card) in deck.cards().iter().enumerate() {
+            assert_eq!(card_to_bits(*card), i as u8);
+            assert!(card_from_bits(i as u8).unwrap().is_same_card(card));
+        }
+        assert!(card_from_bits(52).is_err());
+
+        let hand = parse_cards("Ah Kh 2c 7d Ts Ts 3h").unwrap();
+        let packed = pack(&hand).unwrap();
+        assert!(packed < 1 << 42);
+        assert!(same_cards(&unpack(packed, 7).unwrap(), &hand));
+        assert!(unpack(packed, 5).is_err());
+        assert!(pack(&Deck::new().cards()[..11]).is_err());
+
+        assert_eq!(mask(&hand).unwrap_err(), "Ts shows up twice");
+        let hand = parse_cards("Ah Kh 2c 7d Ts").unwrap();
+        let mask = mask(&hand).unwrap();
+        assert_eq!(mask.count_ones(), 5);
+        let from_mask = from_mask(mask).unwrap();
+        assert_eq!(
+            from_mask.iter().map(Card::to_string).collect::<Vec<_>>(),
+            ["2c", "7d", "Kh", "Ah", "Ts"]
+        );
+        assert!(super::from_mask(1 << 52).is_err());
+    }
+
+    #[test]
+    fn encoding_combination_index() {
+        assert_eq!(binomial(52, 5), 2_598_960);
+        assert_eq!(binomial(52, 7), 133_784_560);
+
+        let five = |index| from_combination_index(index, 5).unwrap();
+        assert!(same_cards(
+            &five(0),
+            &parse_cards("2c 3c 4c 5c 6c").unwrap()
+        ));
+        assert!(same_cards(
+            &five(2_598_959),
+            &parse_cards("Ts Js Qs Ks As").unwrap()
+        ));
+        assert!(from_combination_index(2_598_960, 5).is_err());
+        assert!(from_combination_index(0, 8).is_err());
+
+        // Every index comes back from the cards it stands for.
+        for index in (0..2_598_960).step_by(997).chain([2_598_959]) {
+            assert_eq!(combination_index(&five(index)).unwrap(), index);
+        }
+
+        let mut rng = Rng::new(5);
+        for _ in 0..1000 {
+            let mut deck = Deck::new();
+            deck.shuffle(&mut rng);
+            let seven: Vec<Card> = (0..7).map(|_| deck.deal().unwrap()).collect();
+            let index = combination_index(&seven).unwrap();
+            assert!((index as u64) < binomial(52, 7));
+
+            let mut sorted = seven.clone();
+            sorted.sort_by_key(|card| card_to_bits(*card));
+            assert!(same_cards(
+                &from_combination_index(index, 7).unwrap(),
+                &sorted
+            ));
+
+            let mut reordered = seven.clone();
+            reordered.reverse();
+            assert_eq!(combination_index(&reordered).unwrap(), index);
+        }
+
+        assert!(combination_index(&parse_cards("Ah Ah").unwrap()).is_err());
+        assert!(combination_index(&[]).is_err());
+    }
+
+    #[test]
+    fn encoding_stream() {
+        let mut rng = Rng::new(11);
+        let hands: Vec<Vec<Card>> = (0..200)
+            .map(|_| {
+                let mut deck = Deck::new();
+                deck.shuffle(&mut rng);
+                (0..7).map(|_| deck.deal().unwrap()).collect()
+            })
+            .collect();
+
+        for (encoding, record_len) in [
+            (HandEncoding::Packed, 6),
+            (HandEncoding::Mask, 7),
+            (HandEncoding::Index, 4),
+        ] {
+            let mut writer = HandWriter::new(Vec::new(), encoding, 7).unwrap();
+            for hand in &hands {
+                writer.write_hand(hand).unwrap();
+            }
+            let bytes = writer.finish().unwrap();
+            assert_eq!(bytes.len(), 6 + record_len * hands.len());
+
+            let reader = HandReader::new(bytes.as_slice()).unwrap();
+            assert_eq!(reader.encoding(), encoding);
+            assert_eq!(reader.cards_per_hand(), 7);
+            let read: Vec<Vec<Card>> = reader.collect::<io::Result<_>>().unwrap();
+            assert_eq!(read.len(), hands.len());
+
+            for (read, hand) in read.iter().zip(&hands) {
+                if encoding == HandEncoding::Packed {
+                    assert!(same_cards(read, hand));
+                } else {
+                    assert_eq!(super::mask(read).unwrap(), super::mask(hand).unwrap());
+                }
+            }
+
+            let mut reader = HandReader::new(&bytes[..bytes.len() - 1]).unwrap();
+            for _ in 0..hands.len() - 1 {
+                reader.read_hand().unwrap();
+            }
+            assert_eq!(
+                reader.read_hand().unwrap_err().kind(),
+                io::ErrorKind::InvalidData
+            );
+        }
+
+        let mut writer = HandWriter::new(Vec::new(), HandEncoding::Index, 5).unwrap();
+        assert!(writer.write_hand(&hands[0]).is_err());
+        assert!(HandWriter::new(Vec::new(), HandEncoding::Index, 8).is_err());
+        assert!(HandReader::new(&b"PKHS\x03\x05"[..]).is_err());
+        assert!(HandReader::new(&b"nope!!"[..]).is_err());
+    }
+}
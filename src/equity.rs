@@ -0,0 +1,282 @@
+use crate::deck::{Deck, Rng};
+use crate::{Card, PokerHand, combinations, encoding};
+
+/// How Hold'em hands do against each other over the boards that can still come.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Equity {
+    /// Number of boards looked at.
+    pub boards: u64,
+    /// `true` when every possible board was looked at rather than a random sample.
+    pub exact: bool,
+    /// Boards each hand wins outright.
+    pub wins: Vec<u64>,
+    /// Boards each hand splits with at least one other hand.
+    pub ties: Vec<u64>,
+    /// Share of the pot each hand wins on average. Adds up to 1.
+    pub equities: Vec<f64>,
+}
+
+/// Runs `hands` (two hole cards each) against each other on `board` (zero to five cards).
+///
+/// Every board that can still come is dealt when there are at most `samples` of them, which
+/// gives exact numbers. Otherwise `samples` random boards are dealt from the `seed`.
+pub fn equity(
+    hands: &[Vec<Card>],
+    board: &[Card],
+    samples: u64,
+    seed: u64,
+) -> Result<Equity, String> {
+    if hands.len() < 2 {
+        return Err("Equity needs at least two hands".to_string());
+    }
+    if let Some(hand) = hands.iter().find(|hand| hand.len() != 2) {
+        return Err(format!("Hands must have 2 cards, not {}", hand.len()));
+    }
+    if board.len() > 5 {
+        return Err(format!(
+            "The board has {} cards, at most 5 fit",
+            board.len()
+        ));
+    }
+    if samples == 0 {
+        return Err("Equity needs at least one board".to_string());
+    }
+
+    let mut known: Vec<Card> = hands.concat();
+    known.extend(board);
+    encoding::mask(&known)?;
+
+    let mut deck = Deck::new();
+    deck.remove(&known);
+    let rest = deck.cards();
+    let missing = 5 - board.len();
+    if rest.len() < missing {
+        return Err(format!(
+            "Only {} cards left for {missing} board cards",
+            rest.len()
+        ));
+    }
+
+    let mut equity = Equity {
+        boards: 0,
+        exact: false,
+        wins: vec![0; hands.len()],
+        ties: vec![0; hands.len()],
+        equities: vec![0.0; hands.len()],
+    };
+    let mut full_board = board.to_vec();
+
+    if encoding::binomial(rest.len() as u32, missing as u32) <= samples {
+        equity.exact = true;
+        for indexes in combinations(rest.len(), missing) {
+            full_board.truncate(board.len());
+            full_board.extend(indexes.iter().map(|&i| rest[i]));
+            equity.add_board(hands, &full_board)?;
+        }
+    } else {
+        let mut rng = Rng::new(seed);
+        let mut rest = rest.to_vec();
+        for _ in 0..samples {
+            // Partial Fisher-Yates: the last `missing` cards become the runout.
+            for i in 0..missing {
+                let last = rest.len() - 1 - i;
+                let j = rng.below(last + 1);
+                rest.swap(last, j);
+            }
+            full_board.truncate(board.len());
+            full_board.extend(&rest[rest.len() - missing..]);
+            equity.add_board(hands, &full_board)?;
+        }
+    }
+
+    for share in &mut equity.equities {
+        *share /= equity.boards as f64;
+    }
+    Ok(equity)
+}
+
+/// Runs `hand` against `opponents` random hands on `board`, dealing `samples` random hands and
+/// boards from the `seed`. The hand comes first in the result, then the opponents.
+pub fn equity_against_random(
+    hand: &[Card],
+    board: &[Card],
+    opponents: usize,
+    samples: u64,
+    seed: u64,
+) -> Result<Equity, String> {
+    if hand.len() != 2 {
+        return Err(format!("Hands must have 2 cards, not {}", hand.len()));
+    }
+    if !(1..=9).contains(&opponents) {
+        return Err(format!("Play against 1 to 9 opponents, not {opponents}"));
+    }
+    if board.len() > 5 {
+        return Err(format!(
+            "The board has {} cards, at most 5 fit",
+            board.len()
+        ));
+    }
+    if samples == 0 {
+        return Err("Equity needs at least one board".to_string());
+    }
+
+    let mut known = hand.to_vec();
+    known.extend(board);
+    encoding::mask(&known)?;
+
+    let mut deck = Deck::new();
+    deck.remove(&known);
+    let mut rest = deck.cards().to_vec();
+    let missing = 5 - board.len();
+    let needed = 2 * opponents + missing;
+
+    let players = opponents + 1;
+    let mut equity = Equity {
+        boards: 0,
+        exact: false,
+        wins: vec![0; players],
+        ties: vec![0; players],
+        equities: vec![0.0; players],
+    };
+    let mut rng = Rng::new(seed);
+    let mut hands = vec![hand.to_vec(); players];
+    let mut full_board = board.to_vec();
+    for _ in 0..samples {
+        for i in 0..needed {
+            let last = rest.len() - 1 - i;
+            let j = rng.below(last + 1);
+            rest.swap(last, j);
+        }
+        let dealt = &rest[rest.len() - needed..];
+        for (opponent, cards) in hands[1..].iter_mut().zip(dealt.chunks(2)) {
+            opponent.clear();
+            opponent.extend(cards);
+        }
+        full_board.truncate(board.len());
+        full_board.extend(&dealt[2 * opponents..]);
+        equity.add_board(&hands, &full_board)?;
+    }
+
+    for share in &mut equity.equities {
+        *share /= equity.boards as f64;
+    }
+    Ok(equity)
+}
+
+impl Equity {
+    fn add_board(&mut self, hands: &[Vec<Card>], board: &[Card]) -> Result<(), String> {
+        let mut best = Vec::with_capacity(hands.len());
+        for hand in hands {
+            let mut cards = hand.clone();
+            cards.extend(board);
+            best.push(PokerHand::best_of(&cards)?);
+        }
+
+        let top = best.iter().max().expect("There are at least two hands");
+        let winners: Vec<usize> = (0..best.len()).filter(|&i| best[i] == *top).collect();
+        for &winner in &winners {
+            if winners.len() == 1 {
+                self.wins[winner] += 1;
+            } else {
+                self.ties[winner] += 1;
+            }
+            self.equities[winner] += 1.0 / winners.len() as f64;
+        }
+
+        self.boards += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_cards;
+
+    fn hands(text: &[&str]) -> Vec<Vec<Card>> {
+        text.iter().map(|hand| parse_cards(hand).unwrap()).collect()
+    }
+
+    fn equity_of(text: &[&str], board: &str) -> Equity {
+        equity(&hands(text), &parse_cards(board).unwrap(), 1000, 0).unwrap()
+    }
+
+    #[test]
+    fn equity_exact() {
+        // Aces against kings on the turn: only the two kings left in the deck save the kings.
+        let equity = equity(
+            &hands(&["AhAd", "KhKd"]),
+            &parse_cards("2c 7s 9d Jc").unwrap(),
+            1000,
+            0,
+        )
+        .unwrap();
+        assert!(equity.exact);
+        assert_eq!(equity.boards, 44);
+        assert_eq!(equity.wins, vec![42, 2]);
+        assert_eq!(equity.ties, vec![0, 0]);
+        assert!((equity.equities[0] - 42.0 / 44.0).abs() < 1e-12);
+
+        // Both hands play the broadway straight on the board and split.
+        let equity = equity_of(&["2h3d", "2s3c"], "Ts Jh Qd Kc Ad");
+        assert_eq!((equity.boards, equity.ties.clone()), (1, vec![1, 1]));
+        assert_eq!(equity.equities, vec![0.5, 0.5]);
+
+        let equity = equity_of(&["AhKh", "QsQd", "7c2d"], "2c 7h 9d");
+        assert_eq!(equity.boards, 903);
+        assert!((equity.equities.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn equity_sampled() {
+        let hands = hands(&["AhAd", "7c2d"]);
+        let first = equity(&hands, &[], 2000, 9).unwrap();
+        assert!(!first.exact);
+        assert_eq!(first.boards, 2000);
+        assert!((0.83..0.93).contains(&first.equities[0]), "{first:?}");
+        assert_eq!(equity(&hands, &[], 2000, 9).unwrap(), first);
+    }
+
+    #[test]
+    fn equity_against_random_hands() {
+        let aces = parse_cards("AhAd").unwrap();
+        let result = equity_against_random(&aces, &[], 1, 2000, 3).unwrap();
+        assert_eq!(result.boards, 2000);
+        assert_eq!(result.equities.len(), 2);
+        assert!((0.8..0.9).contains(&result.equities[0]), "{result:?}");
+
+        let result = equity_against_random(&aces, &[], 4, 1000, 3).unwrap();
+        assert!((0.5..0.62).contains(&result.equities[0]), "{result:?}");
+        assert!((result.equities.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+
+        let board = parse_cards("2c 7h 9d As Kd").unwrap();
+        let result = equity_against_random(&aces, &board, 1, 100, 3).unwrap();
+        assert_eq!(result.wins[0] + result.ties[0], 100, "Top set on the river");
+
+        assert!(equity_against_random(&aces, &[], 0, 100, 3).is_err());
+        assert!(equity_against_random(&aces, &board[..1], 1, 100, 3).is_ok());
+        assert!(equity_against_random(&aces[..1], &[], 1, 100, 3).is_err());
+    }
+
+    #[test]
+    fn equity_errors() {
+        let board = parse_cards("2c 7h 9d").unwrap();
+        assert!(equity(&hands(&["AhKh"]), &board, 100, 0).is_err());
+        assert!(equity(&hands(&["AhKh", "Qs"]), &board, 100, 0).is_err());
+        assert_eq!(
+            equity(&hands(&["AhKh", "2cQd"]), &board, 100, 0).unwrap_err(),
+            "2c shows up twice"
+        );
+        assert!(equity(&hands(&["AhKh", "QsQd"]), &board, 0, 0).is_err());
+
+        // 24 hands leave 4 cards, one short of a board.
+        let crowded: Vec<Vec<Card>> = Deck::new().cards()[..48]
+            .chunks(2)
+            .map(|hand| hand.to_vec())
+            .collect();
+        assert_eq!(
+            equity(&crowded, &[], 100, 0).unwrap_err(),
+            "Only 4 cards left for 5 board cards"
+        );
+    }
+}
@@ -0,0 +1,406 @@
+use core::cmp::Ordering;
+use core::fmt;
+
+use crate::{Card, PokerHand, PokerHandType, Suit, rank_bits, straight_high};
+
+/// A five card hand's type, with its cards in the order `PokerHand::sort_hand` puts them: most
+/// important first, and a wheel as 5 4 3 2 A.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandValue {
+    hand_type: PokerHandType,
+    cards: [Card; 5],
+}
+
+impl HandValue {
+    pub const fn hand_type(&self) -> &PokerHandType {
+        &self.hand_type
+    }
+
+    pub const fn cards(&self) -> &[Card; 5] {
+        &self.cards
+    }
+
+    /// `cmp` that also works in a `const` context.
+    pub const fn compare(&self, other: &Self) -> Ordering {
+        let (a, b) = (strength(&self.hand_type), strength(&other.hand_type));
+        if a != b {
+            return if a > b {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+        }
+
+        let mut i = 0;
+        while i < 5 {
+            let (a, b) = (self.cards[i].rank, other.cards[i].rank);
+            if a != b {
+                return if a > b {
+                    Ordering::Greater
+                } else {
+                    Ordering::Less
+                };
+            }
+            i += 1;
+        }
+        Ordering::Equal
+    }
+}
+
+impl Ord for HandValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.compare(other)
+    }
+}
+
+impl PartialOrd for HandValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<HandValue> for PokerHand {
+    fn from(value: HandValue) -> Self {
+        PokerHand {
+            cards: HandBuilder::from(value.cards),
+            poker_hand_type: Some(value.hand_type),
+        }
+    }
+}
+
+const fn strength(hand_type: &PokerHandType) -> u8 {
+    match hand_type {
+        PokerHandType::HighCard => 0,
+        PokerHandType::OnePair => 1,
+        PokerHandType::TwoPair => 2,
+        PokerHandType::ThreeOfAKind => 3,
+        PokerHandType::Straight => 4,
+        PokerHandType::Flush => 5,
+        PokerHandType::FullHouse => 6,
+        PokerHandType::FourOfAKind => 7,
+        PokerHandType::StraightFlush => 8,
+        PokerHandType::RoyalFlush => 9,
+    }
+}
+
+/// Works out what five cards make without allocating.
+pub const fn evaluate(cards: &[Card; 5]) -> HandValue {
+    let mut counts = [0u8; 15];
+    let mut flush = true;
+    let mut i = 0;
+    while i < 5 {
+        counts[cards[i].rank as usize] += 1;
+        flush &= cards[i].suit as u8 == cards[0].suit as u8;
+        i += 1;
+    }
+
+    // Bigger groups first, then higher ranks. Insertion sort keeps cards of the same rank in
+    // the order they came in.
+    let mut sorted = *cards;
+    let mut i = 1;
+    while i < 5 {
+        let mut j = i;
+        while j > 0 && goes_before(&sorted[j], &sorted[j - 1], &counts) {
+            sorted.swap(j, j - 1);
+            j -= 1;
+        }
+        i += 1;
+    }
+
+    let most = counts[sorted[0].rank as usize];
+    let second = if most < 5 {
+        counts[sorted[most as usize].rank as usize]
+    } else {
+        0
+    };
+    let straight = match straight_high(rank_bits(cards)) {
+        Some(high) if most == 1 => high,
+        _ => 0,
+    };
+    if straight == 5 {
+        // A wheel: the ace goes last.
+        let ace = sorted[0];
+        let mut i = 0;
+        while i < 4 {
+            sorted[i] = sorted[i + 1];
+            i += 1;
+        }
+        sorted[4] = ace;
+    }
+
+    let hand_type = match (most, second) {
+        (4, _) => PokerHandType::FourOfAKind,
+        (3, 2) => PokerHandType::FullHouse,
+        (3, _) => PokerHandType::ThreeOfAKind,
+        (2, 2) => PokerHandType::TwoPair,
+        (2, _) => PokerHandType::OnePair,
+        _ => match (straight, flush) {
+            (14, true) => PokerHandType::RoyalFlush,
+            (0, true) => PokerHandType::Flush,
+            (_, true) => PokerHandType::StraightFlush,
+            (0, false) => PokerHandType::HighCard,
+            (_, false) => PokerHandType::Straight,
+        },
+    };
+
+    HandValue {
+        hand_type,
+        cards: sorted,
+    }
+}
+
+const fn goes_before(a: &Card, b: &Card, counts: &[u8; 15]) -> bool {
+    let (a_count, b_count) = (counts[a.rank as usize], counts[b.rank as usize]);
+    a_count > b_count || (a_count == b_count && a.rank > b.rank)
+}
+
+/// The strongest five cards out of `cards`, `None` with fewer than five. Like
+/// `PokerHand::best_of`, but without allocating.
+pub const fn best_of(cards: &[Card]) -> Option<HandValue> {
+    let n = cards.len();
+    if n < 5 {
+        return None;
+    }
+
+    let mut best: Option<HandValue> = None;
+    let mut indexes = [0, 1, 2, 3, 4];
+    loop {
+        let hand = [
+            cards[indexes[0]],
+            cards[indexes[1]],
+            cards[indexes[2]],
+            cards[indexes[3]],
+            cards[indexes[4]],
+        ];
+        let value = evaluate(&hand);
+        let better = match &best {
+            Some(best) => matches!(value.compare(best), Ordering::Greater),
+            None => true,
+        };
+        if better {
+            best = Some(value);
+        }
+
+        // Next combination in lexicographic order, as `combinations` hands them out.
+        let mut i = 5;
+        while i > 0 && indexes[i - 1] == n - 5 + i - 1 {
+            i -= 1;
+        }
+        if i == 0 {
+            return best;
+        }
+        indexes[i - 1] += 1;
+        let mut j = i;
+        while j < 5 {
+            indexes[j] = indexes[j - 1] + 1;
+            j += 1;
+        }
+    }
+}
+
+/// Up to five cards kept on the stack and added one at a time. `PokerHand` keeps its cards
+/// in one.
+#[derive(Clone, Copy)]
+pub struct HandBuilder {
+    cards: [Card; 5],
+    len: usize,
+}
+
+impl Default for HandBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HandBuilder {
+    pub const fn new() -> Self {
+        HandBuilder {
+            // Placeholders until cards are added.
+            cards: [Card {
+                rank: 2,
+                suit: Suit::Club,
+            }; 5],
+            len: 0,
+        }
+    }
+
+    pub const fn add_card(&mut self, card: Card) -> Result<(), &'static str> {
+        if self.len == 5 {
+            return Err("Max hand limit is 5");
+        }
+
+        self.cards[self.len] = card;
+        self.len += 1;
+        Ok(())
+    }
+
+    pub const fn cards(&self) -> &[Card] {
+        self.cards.split_at(self.len).0
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn build(self) -> Result<[Card; 5], &'static str> {
+        if self.len != 5 {
+            return Err("Must have 5 Cards to build a hand");
+        }
+        Ok(self.cards)
+    }
+
+    pub const fn evaluate(&self) -> Result<HandValue, &'static str> {
+        match self.build() {
+            Ok(cards) => Ok(evaluate(&cards)),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+impl From<[Card; 5]> for HandBuilder {
+    fn from(cards: [Card; 5]) -> Self {
+        HandBuilder { cards, len: 5 }
+    }
+}
+
+/// Only the cards added so far count, not the placeholders after them.
+impl PartialEq for HandBuilder {
+    fn eq(&self, other: &Self) -> bool {
+        self.cards() == other.cards()
+    }
+}
+
+impl Eq for HandBuilder {}
+
+impl fmt::Debug for HandBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.cards()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use crate::parse_cards;
+
+    #[cfg(feature = "std")]
+    fn value(text: &str) -> HandValue {
+        evaluate(&parse_cards(text).unwrap().try_into().unwrap())
+    }
+
+    #[cfg(feature = "std")]
+    fn text(cards: &[Card]) -> String {
+        cards
+            .iter()
+            .map(|card| card.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    const fn spade(rank: u8) -> Card {
+        match Card::new(rank, Suit::Spade) {
+            Ok(card) => card,
+            Err(_) => panic!("not a rank"),
+        }
+    }
+
+    // Built and evaluated at compile time.
+    const ROYAL: HandValue = {
+        let mut builder = HandBuilder::new();
+        let ranks = [14, 11, 13, 10, 12];
+        let mut i = 0;
+        while i < ranks.len() {
+            assert!(builder.add_card(spade(ranks[i])).is_ok());
+            i += 1;
+        }
+        match builder.evaluate() {
+            Ok(value) => value,
+            Err(_) => panic!("five cards"),
+        }
+    };
+
+    #[test]
+    fn evaluate_in_const() {
+        assert_eq!(*ROYAL.hand_type(), PokerHandType::RoyalFlush);
+        assert_eq!(ROYAL.cards().map(|card| card.rank()), [14, 13, 12, 11, 10]);
+        assert!(matches!(ROYAL.compare(&ROYAL), Ordering::Equal));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn evaluate_matches_sort_hand() {
+        assert_eq!(text(ROYAL.cards()), "As Ks Qs Js Ts");
+
+        let cases = [
+            ("2c 3d 4h 5s Ac", "5s 4h 3d 2c Ac", PokerHandType::Straight),
+            ("Kd 3c Kh 3s 3d", "3c 3s 3d Kd Kh", PokerHandType::FullHouse),
+            ("9c 2d 9h Ts 2s", "9c 9h 2d 2s Ts", PokerHandType::TwoPair),
+            ("4c 8c Jc 2c 5c", "Jc 8c 5c 4c 2c", PokerHandType::Flush),
+            ("7h 7s Ad Kc 2h", "7h 7s Ad Kc 2h", PokerHandType::OnePair),
+            (
+                "6s 7s 8s 9s Ts",
+                "Ts 9s 8s 7s 6s",
+                PokerHandType::StraightFlush,
+            ),
+            (
+                "Qh 3h Qd Qs Qc",
+                "Qh Qd Qs Qc 3h",
+                PokerHandType::FourOfAKind,
+            ),
+            ("Ac Kd 9h 5s 2c", "Ac Kd 9h 5s 2c", PokerHandType::HighCard),
+        ];
+        for (hand, sorted, hand_type) in cases {
+            let value = value(hand);
+            assert_eq!(
+                (text(value.cards()), value.hand_type()),
+                (sorted.to_string(), &hand_type)
+            );
+
+            let mut poker_hand = PokerHand::new();
+            for card in parse_cards(hand).unwrap() {
+                poker_hand.add_card(card).unwrap();
+            }
+            poker_hand.sort_hand().unwrap();
+            assert_eq!(text(poker_hand.cards()), sorted);
+        }
+
+        assert!(value("5c 4d 3h 2s Ac") < value("6c 5d 4h 3s 2c"));
+        assert!(value("Ac Ad Kh Ks 2c") > value("Ac Ad Qh Qs Kc"));
+        assert_eq!(
+            value("Ac Ad Kh Ks 2c").cmp(&value("Ah As Kc Kd 2d")),
+            Ordering::Equal
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn best_of_and_builder() {
+        let cards = parse_cards("2c Kd 3h Kh 4s Ad 5c").unwrap();
+        let best = best_of(&cards).unwrap();
+        assert_eq!(text(best.cards()), "5c 4s 3h 2c Ad");
+        assert_eq!(
+            PokerHand::from(best.clone()),
+            PokerHand::best_of(&cards).unwrap()
+        );
+        assert!(best_of(&cards[..4]).is_none());
+
+        let mut builder = HandBuilder::new();
+        assert!(builder.is_empty());
+        assert!(builder.evaluate().is_err());
+        for &card in &cards[..5] {
+            builder.add_card(card).unwrap();
+        }
+        assert_eq!(builder.len(), 5);
+        assert_eq!(text(builder.cards()), "2c Kd 3h Kh 4s");
+        assert_eq!(builder.add_card(cards[5]), Err("Max hand limit is 5"));
+        assert_eq!(
+            *builder.evaluate().unwrap().hand_type(),
+            PokerHandType::OnePair
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use crate::betting::NoLimit;
+use crate::deck::Deck;
+use crate::holdem::{Action, HoldemConfig, HoldemGame, Street};
+use crate::{Card, PokerHandType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryAction {
+    pub street: Street,
+    pub seat: usize,
+    pub action: Action,
+}
+
+/// A hand that made it to showdown, with the category it was scored as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShownHand {
+    pub seat: usize,
+    pub hole_cards: Vec<Card>,
+    pub best_five: Vec<Card>,
+    pub hand_type: PokerHandType,
+}
+
+/// Everything needed to play a hand of Hold'em again: who sat where with how many chips, the
+/// cards, every action and how the pots were paid out.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HandHistory {
+    pub config: HoldemConfig,
+    pub button: usize,
+    /// Stacks before antes and blinds, one per seat.
+    pub starting_stacks: Vec<u32>,
+    /// Empty for seats that were not dealt in.
+    pub hole_cards: Vec<Vec<Card>>,
+    pub board: Vec<Card>,
+    pub actions: Vec<HistoryAction>,
+    pub showdown: Vec<ShownHand>,
+    pub winnings: Vec<u32>,
+}
+
+impl HandHistory {
+    pub fn actions_on(&self, street: Street) -> impl Iterator<Item = &HistoryAction> {
+        self.actions
+            .iter()
+            .filter(move |action| action.street == street)
+    }
+
+    // The deck that deals the recorded cards in the same order the table dealt them. Burn
+    // cards were never seen, so any unused card stands in for them.
+    fn stacked_deck(&self) -> Deck {
+        let seats = self.hole_cards.len();
+        let mut cards = Vec::new();
+        for round in 0..2 {
+            for offset in 1..=seats {
+                let seat = (self.button + offset) % seats;
+                if let Some(card) = self.hole_cards[seat].get(round) {
+                    cards.push(*card);
+                }
+            }
+        }
+
+        let mut unused = Deck::new();
+        unused.remove(&cards);
+        unused.remove(&self.board);
+        for (i, card) in self.board.iter().enumerate() {
+            // A card is burned before the flop, the turn and the river.
+            if matches!(i, 0 | 3 | 4) {
+                cards.push(unused.deal().expect("Plenty of cards are left to burn"));
+            }
+            cards.push(*card);
+        }
+
+        Deck::stacked(&cards)
+    }
+}
+
+/// Plays a recorded hand back one action at a time on a fresh table.
+///
+/// The replay table always validates with `NoLimit`, which accepts every action that was legal
+/// under pot-limit or fixed-limit as well.
+#[derive(Debug)]
+pub struct Replayer<'a> {
+    history: &'a HandHistory,
+    game: HoldemGame,
+    position: usize,
+}
+
+impl<'a> Replayer<'a> {
+    pub fn new(history: &'a HandHistory) -> Result<Self, String> {
+        let mut game =
+            HoldemGame::with_betting_structure(&history.starting_stacks, history.config, NoLimit)?;
+        game.start_hand_at(history.stacked_deck(), history.button)?;
+
+        Ok(Replayer {
+            history,
+            game,
+            position: 0,
+        })
+    }
+
+    /// The table as it was after the first `position` actions.
+    pub fn game(&self) -> &HoldemGame {
+        &self.game
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position == self.history.actions.len()
+    }
+
+    /// Applies the next recorded action. Returns `false` once every action was applied.
+    pub fn step(&mut self) -> Result<bool, String> {
+        let Some(recorded) = self.history.actions.get(self.position) else {
+            return Ok(false);
+        };
+
+        if self.game.to_act() != Some(recorded.seat) || self.game.street() != recorded.street {
+            return Err(format!(
+                "Action {} was recorded for seat {} on the {:?}, but the replay has seat {:?} to act on the {:?}",
+                self.position,
+                recorded.seat,
+                recorded.street,
+                self.game.to_act(),
+                self.game.street()
+            ));
+        }
+        self.game.act(recorded.action).map_err(|error| {
+            format!("Action {} ({:?}): {error}", self.position, recorded.action)
+        })?;
+
+        self.position += 1;
+        Ok(true)
+    }
+
+    /// Goes back one action by playing the hand again from the start up to the action before.
+    /// Returns `false` when already at the start.
+    pub fn step_back(&mut self) -> Result<bool, String> {
+        if self.position == 0 {
+            return Ok(false);
+        }
+
+        let target = self.position - 1;
+        *self = Replayer::new(self.history)?;
+        while self.position < target {
+            self.step()?;
+        }
+        Ok(true)
+    }
+
+    /// Plays the whole hand back and checks that the board, showdown hand types and payouts
+    /// match what was recorded.
+    pub fn verify(history: &'a HandHistory) -> Result<(), String> {
+        let mut replayer = Replayer::new(history)?;
+        while replayer.step()? {}
+
+        let game = replayer.game;
+        if !game.is_hand_over() {
+            return Err("The recorded actions do not finish the hand".to_string());
+        }
+        if !same_cards(game.board(), &history.board) {
+            return Err(format!(
+                "Board {:?} does not match the recorded {:?}",
+                game.board(),
+                history.board
+            ));
+        }
+
+        let replayed = &game.history().showdown;
+        if replayed.len() != history.showdown.len() {
+            return Err(format!(
+                "{} hands were shown down, but {} were recorded",
+                replayed.len(),
+                history.showdown.len()
+            ));
+        }
+        for (replayed, recorded) in replayed.iter().zip(&history.showdown) {
+            if replayed.seat != recorded.seat || replayed.hand_type != recorded.hand_type {
+                return Err(format!(
+                    "Seat {} shows {:?}, but seat {} was recorded with {:?}",
+                    replayed.seat, replayed.hand_type, recorded.seat, recorded.hand_type
+                ));
+            }
+        }
+
+        if game.winnings() != history.winnings.as_slice() {
+            return Err(format!(
+                "Payouts {:?} do not match the recorded {:?}",
+                game.winnings(),
+                history.winnings
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn same_cards(a: &[Card], b: &[Card]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.is_same_card(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: HoldemConfig = HoldemConfig {
+        small_blind: 1,
+        big_blind: 2,
+        ante: 0,
+    };
+
+    fn played_hand(seed: u64) -> HoldemGame {
+        let mut game = HoldemGame::new(&[100, 80, 120], CONFIG).unwrap();
+        game.start_hand(Deck::shuffled(seed)).unwrap();
+
+        game.act(Action::Raise(6)).unwrap();
+        game.act(Action::Call).unwrap();
+        game.act(Action::Call).unwrap();
+        game.act(Action::Check).unwrap();
+        game.act(Action::Bet(10)).unwrap();
+        game.act(Action::Fold).unwrap();
+        game.act(Action::Call).unwrap();
+        while !game.is_hand_over() {
+            game.act(Action::Check).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn history_records_the_hand() {
+        let game = played_hand(31);
+        let history = game.history();
+
+        assert_eq!(history.starting_stacks, vec![100, 80, 120]);
+        assert_eq!(history.button, 0);
+        assert!(history.hole_cards.iter().all(|cards| cards.len() == 2));
+        assert_eq!(history.board.len(), 5);
+        assert_eq!(history.actions_on(Street::Preflop).count(), 3);
+        assert_eq!(history.actions_on(Street::Flop).count(), 4);
+        assert_eq!(
+            history.actions[4],
+            HistoryAction {
+                street: Street::Flop,
+                seat: 2,
+                action: Action::Bet(10),
+            }
+        );
+        assert_eq!(history.showdown.len(), 2);
+        assert_eq!(history.winnings.iter().sum::<u32>(), 38);
+    }
+
+    #[test]
+    fn history_replay() {
+        for seed in 0..20 {
+            let game = played_hand(seed);
+            let history = game.history();
+            Replayer::verify(history).unwrap();
+
+            let mut replayer = Replayer::new(history).unwrap();
+            assert_eq!(replayer.game().to_act(), Some(0));
+            while replayer.step().unwrap() {}
+            assert!(replayer.is_finished());
+
+            replayer.step_back().unwrap();
+            assert_eq!(replayer.position(), history.actions.len() - 1);
+            assert!(!replayer.game().is_hand_over());
+            replayer.step().unwrap();
+            assert!(replayer.game().is_hand_over());
+        }
+    }
+
+    #[test]
+    fn history_replay_catches_changes() {
+        let game = played_hand(3);
+
+        let mut history = game.history().clone();
+        history.winnings.swap(0, 2);
+        history.winnings.swap(1, 2);
+        assert!(Replayer::verify(&history).is_err());
+
+        let mut history = game.history().clone();
+        history.showdown[0].hand_type = PokerHandType::RoyalFlush;
+        assert!(Replayer::verify(&history).is_err());
+
+        let mut history = game.history().clone();
+        history.actions[0].action = Action::Check;
+        assert!(Replayer::verify(&history).is_err());
+    }
+}
@@ -0,0 +1,681 @@
+use crate::betting::{ActionError, BettingState, BettingStructure, NoLimit};
+use crate::deck::Deck;
+use crate::history::{HandHistory, HistoryAction, ShownHand};
+use crate::pot::{award_pots, side_pots};
+use crate::{Card, PokerHand};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Street {
+    Preflop,
+    Flop,
+    Turn,
+    River,
+    Showdown,
+}
+
+/// `Bet` and `Raise` hold the total amount the player has in front of them for the street
+/// afterwards ("raise to"), not the amount added on top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Fold,
+    Check,
+    Call,
+    Bet(u32),
+    Raise(u32),
+}
+
+/// What the player to act is allowed to do, with the amounts allowed for bets and raises.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegalAction {
+    Fold,
+    Check,
+    Call(u32),
+    Bet { min: u32, max: u32 },
+    Raise { min: u32, max: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HoldemConfig {
+    pub small_blind: u32,
+    pub big_blind: u32,
+    pub ante: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Seat {
+    pub stack: u32,
+    hole_cards: Vec<Card>,
+    // Chips put in on the current street
+    street_bet: u32,
+    // Chips put in over the whole hand, antes included
+    committed: u32,
+    folded: bool,
+    // Acted since the last full bet or raise
+    acted: bool,
+}
+
+impl Seat {
+    fn new(stack: u32) -> Self {
+        Seat {
+            stack,
+            hole_cards: Vec::new(),
+            street_bet: 0,
+            committed: 0,
+            folded: true,
+            acted: false,
+        }
+    }
+
+    pub fn hole_cards(&self) -> &[Card] {
+        &self.hole_cards
+    }
+
+    pub fn street_bet(&self) -> u32 {
+        self.street_bet
+    }
+
+    pub fn committed(&self) -> u32 {
+        self.committed
+    }
+
+    pub fn is_folded(&self) -> bool {
+        self.folded
+    }
+
+    pub fn is_all_in(&self) -> bool {
+        !self.folded && self.stack == 0
+    }
+
+    fn can_act(&self) -> bool {
+        !self.folded && self.stack > 0
+    }
+
+    fn put_in(&mut self, amount: u32) -> u32 {
+        let amount = amount.min(self.stack);
+        self.stack -= amount;
+        self.street_bet += amount;
+        self.committed += amount;
+        amount
+    }
+}
+
+/// A Texas Hold'em table, no-limit unless another `BettingStructure` is given. Seats are
+/// indexed clockwise, and every hand runs from `start_hand` through the betting rounds driven
+/// by `act` until `is_hand_over`.
+#[derive(Debug)]
+pub struct HoldemGame {
+    config: HoldemConfig,
+    betting: Box<dyn BettingStructure>,
+    seats: Vec<Seat>,
+    button: Option<usize>,
+    deck: Deck,
+    board: Vec<Card>,
+    street: Street,
+    to_act: Option<usize>,
+    current_bet: u32,
+    min_raise: u32,
+    bets_this_street: u32,
+    winnings: Vec<u32>,
+    history: HandHistory,
+}
+
+impl HoldemGame {
+    pub fn new(stacks: &[u32], config: HoldemConfig) -> Result<Self, &'static str> {
+        Self::with_betting_structure(stacks, config, NoLimit)
+    }
+
+    pub fn with_betting_structure(
+        stacks: &[u32],
+        config: HoldemConfig,
+        betting: impl BettingStructure + 'static,
+    ) -> Result<Self, &'static str> {
+        if !(2..=10).contains(&stacks.len()) {
+            return Err("A table needs between 2 and 10 seats");
+        }
+        if config.big_blind == 0 || config.small_blind > config.big_blind {
+            return Err("The big blind must be positive and at least the small blind");
+        }
+
+        Ok(HoldemGame {
+            config,
+            betting: Box::new(betting),
+            seats: stacks.iter().map(|&stack| Seat::new(stack)).collect(),
+            button: None,
+            deck: Deck::new(),
+            board: Vec::new(),
+            street: Street::Showdown,
+            to_act: None,
+            current_bet: 0,
+            min_raise: 0,
+            bets_this_street: 0,
+            winnings: vec![0; stacks.len()],
+            history: HandHistory::default(),
+        })
+    }
+
+    /// Moves the button, posts antes and blinds, deals the hole cards from `deck` and hands
+    /// the action to the first player.
+    pub fn start_hand(&mut self, deck: Deck) -> Result<(), &'static str> {
+        if !self.is_hand_over() {
+            return Err("The current hand is not over");
+        }
+        if self.seats.iter().filter(|seat| seat.stack > 0).count() < 2 {
+            return Err("Need at least 2 players with chips to start a hand");
+        }
+
+        let after = self.button.unwrap_or(self.seats.len() - 1);
+        let button = (1..=self.seats.len())
+            .map(|offset| (after + offset) % self.seats.len())
+            .find(|&seat| self.seats[seat].stack > 0)
+            .expect("At least 2 players have chips");
+        self.start_hand_at(deck, button)
+    }
+
+    /// Starts a hand with the button on `button`, e.g. to put a replay or a saved session back
+    /// in place.
+    pub fn start_hand_at(&mut self, deck: Deck, button: usize) -> Result<(), &'static str> {
+        if !self.is_hand_over() {
+            return Err("The current hand is not over");
+        }
+        if self.seats.get(button).is_none_or(|seat| seat.stack == 0) {
+            return Err("The button must be on a player with chips");
+        }
+
+        self.history = HandHistory {
+            config: self.config,
+            button,
+            starting_stacks: self.seats.iter().map(|seat| seat.stack).collect(),
+            ..HandHistory::default()
+        };
+        for seat in &mut self.seats {
+            *seat = Seat {
+                folded: seat.stack == 0,
+                ..Seat::new(seat.stack)
+            };
+        }
+        self.deck = deck;
+        self.board.clear();
+        self.winnings = vec![0; self.seats.len()];
+        self.street = Street::Preflop;
+        self.button = Some(button);
+
+        for seat in self.seats.iter_mut().filter(|seat| !seat.folded) {
+            let ante = seat.put_in(self.config.ante);
+            seat.street_bet -= ante;
+        }
+
+        // Heads up the button posts the small blind and acts first before the flop.
+        let small_blind = if self.players_in_hand() == 2 {
+            button
+        } else {
+            self.next_in_hand(button)
+        };
+        let big_blind = self.next_in_hand(small_blind);
+        self.seats[small_blind].put_in(self.config.small_blind);
+        self.seats[big_blind].put_in(self.config.big_blind);
+        self.current_bet = self.config.big_blind;
+        self.min_raise = self.config.big_blind;
+        self.bets_this_street = 1;
+
+        for _ in 0..2 {
+            let mut seat = button;
+            for _ in 0..self.players_in_hand() {
+                seat = self.next_in_hand(seat);
+                let card = self.deck.deal().ok_or("The deck ran out of cards")?;
+                self.seats[seat].hole_cards.push(card);
+            }
+        }
+
+        self.history.hole_cards = self
+            .seats
+            .iter()
+            .map(|seat| seat.hole_cards.clone())
+            .collect();
+        self.to_act = self.next_to_act(big_blind);
+        self.advance()
+    }
+
+    pub fn config(&self) -> HoldemConfig {
+        self.config
+    }
+
+    pub fn seats(&self) -> &[Seat] {
+        &self.seats
+    }
+
+    pub fn button(&self) -> Option<usize> {
+        self.button
+    }
+
+    pub fn board(&self) -> &[Card] {
+        &self.board
+    }
+
+    pub fn street(&self) -> Street {
+        self.street
+    }
+
+    pub fn to_act(&self) -> Option<usize> {
+        self.to_act
+    }
+
+    pub fn current_bet(&self) -> u32 {
+        self.current_bet
+    }
+
+    pub fn min_raise(&self) -> u32 {
+        self.min_raise
+    }
+
+    pub fn pot(&self) -> u32 {
+        self.seats.iter().map(|seat| seat.committed).sum()
+    }
+
+    pub fn is_hand_over(&self) -> bool {
+        self.street == Street::Showdown
+    }
+
+    /// Chips each seat won in the last finished hand. They are already added to the stacks.
+    pub fn winnings(&self) -> &[u32] {
+        &self.winnings
+    }
+
+    /// Everything that happened in the current hand so far, or in the last one once it is over.
+    pub fn history(&self) -> &HandHistory {
+        &self.history
+    }
+
+    /// The spot the player to act is in, `None` when no one is left to act.
+    pub fn betting_state(&self) -> Option<BettingState> {
+        let player = &self.seats[self.to_act?];
+
+        Some(BettingState {
+            street: self.street,
+            big_blind: self.config.big_blind,
+            pot: self.pot(),
+            current_bet: self.current_bet,
+            min_raise: self.min_raise,
+            bets_this_street: self.bets_this_street,
+            street_bet: player.street_bet,
+            stack: player.stack,
+            can_raise: !player.acted,
+        })
+    }
+
+    pub fn legal_actions(&self) -> Vec<LegalAction> {
+        match self.betting_state() {
+            Some(state) => self.betting.legal_actions(&state),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn act(&mut self, action: Action) -> Result<(), ActionError> {
+        let seat = self.to_act.ok_or(ActionError::NoActionPending)?;
+        let state = self.betting_state().ok_or(ActionError::NoActionPending)?;
+        self.betting.validate(&state, action)?;
+        self.history.actions.push(HistoryAction {
+            street: self.street,
+            seat,
+            action,
+        });
+
+        match action {
+            Action::Fold => self.seats[seat].folded = true,
+            Action::Check => {}
+            Action::Call => {
+                let to_call = self.current_bet - self.seats[seat].street_bet;
+                self.seats[seat].put_in(to_call);
+            }
+            Action::Bet(amount) | Action::Raise(amount) => {
+                let raise_size = amount - self.current_bet;
+                let player = &mut self.seats[seat];
+                player.put_in(amount - player.street_bet);
+                self.current_bet = amount;
+
+                // Only a full raise re-opens the betting for players that already acted.
+                if raise_size >= self.min_raise {
+                    self.min_raise = raise_size;
+                    self.bets_this_street += 1;
+                    for other in &mut self.seats {
+                        other.acted = false;
+                    }
+                }
+            }
+        }
+
+        self.seats[seat].acted = true;
+        self.to_act = self.next_to_act(seat);
+        self.advance().map_err(ActionError::Table)
+    }
+
+    // Moves the hand along once the betting round is over, dealing the board and paying out.
+    fn advance(&mut self) -> Result<(), &'static str> {
+        loop {
+            if self.players_in_hand() == 1 {
+                return self.finish();
+            }
+            if self.to_act.is_some() {
+                return Ok(());
+            }
+
+            for seat in &mut self.seats {
+                seat.street_bet = 0;
+                seat.acted = false;
+            }
+            self.current_bet = 0;
+            self.min_raise = self.config.big_blind;
+            self.bets_this_street = 0;
+
+            let cards = match self.street {
+                Street::Preflop => 3,
+                Street::Flop | Street::Turn => 1,
+                Street::River | Street::Showdown => return self.finish(),
+            };
+            // Burn one
+            self.deck.deal().ok_or("The deck ran out of cards")?;
+            for _ in 0..cards {
+                let card = self.deck.deal().ok_or("The deck ran out of cards")?;
+                self.board.push(card);
+            }
+            self.street = match self.street {
+                Street::Preflop => Street::Flop,
+                Street::Flop => Street::Turn,
+                _ => Street::River,
+            };
+
+            // Once everyone but one player is all in, the board just runs out.
+            let button = self.button.expect("A hand is running");
+            if self.seats.iter().filter(|seat| seat.can_act()).count() > 1 {
+                self.to_act = self.next_to_act(button);
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), &'static str> {
+        let showdown = self.players_in_hand() > 1;
+        let mut hands = Vec::with_capacity(self.seats.len());
+        for seat in &self.seats {
+            if seat.folded || !showdown {
+                hands.push(None);
+            } else {
+                let cards: Vec<Card> = seat.hole_cards.iter().chain(&self.board).copied().collect();
+                hands.push(Some(PokerHand::best_of(&cards)?));
+            }
+        }
+
+        let contributions: Vec<u32> = self.seats.iter().map(|seat| seat.committed).collect();
+        let folded: Vec<bool> = self.seats.iter().map(|seat| seat.folded).collect();
+        let pots = side_pots(&contributions, &folded);
+        let button = self.button.expect("A hand is running");
+
+        self.winnings = award_pots(&pots, &hands, button)?;
+        for (seat, won) in self.seats.iter_mut().zip(&self.winnings) {
+            seat.stack += won;
+        }
+
+        self.history.board = self.board.clone();
+        self.history.winnings = self.winnings.clone();
+        self.history.showdown = hands
+            .into_iter()
+            .enumerate()
+            .filter_map(|(seat, hand)| {
+                let hand = hand?;
+                Some(ShownHand {
+                    seat,
+                    hole_cards: self.seats[seat].hole_cards.clone(),
+                    hand_type: hand.poker_hand_type.clone()?,
+                    best_five: hand.cards().to_vec(),
+                })
+            })
+            .collect();
+
+        self.street = Street::Showdown;
+        self.to_act = None;
+        Ok(())
+    }
+
+    fn players_in_hand(&self) -> usize {
+        self.seats.iter().filter(|seat| !seat.folded).count()
+    }
+
+    fn next_in_hand(&self, seat: usize) -> usize {
+        (1..=self.seats.len())
+            .map(|offset| (seat + offset) % self.seats.len())
+            .find(|&i| !self.seats[i].folded)
+            .expect("At least one player is in the hand")
+    }
+
+    // The next player clockwise from `seat` that still has to act in this betting round.
+    fn next_to_act(&self, seat: usize) -> Option<usize> {
+        (1..=self.seats.len())
+            .map(|offset| (seat + offset) % self.seats.len())
+            .find(|&i| {
+                let player = &self.seats[i];
+                let facing_bet = player.street_bet < self.current_bet;
+                // No one to bet against when everyone else is all in.
+                let opponents = self.seats.iter().filter(|seat| seat.can_act()).count() > 1;
+                player.can_act() && (facing_bet || (!player.acted && opponents))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Suit;
+    use crate::betting::FixedLimit;
+
+    const CONFIG: HoldemConfig = HoldemConfig {
+        small_blind: 1,
+        big_blind: 2,
+        ante: 0,
+    };
+
+    fn card(rank: u8, suit: Suit) -> Card {
+        Card::new(rank, suit).unwrap()
+    }
+
+    // Seat 1 gets AA, seat 0 gets 72 and the board bricks out.
+    fn heads_up_deck() -> Deck {
+        Deck::stacked(&[
+            card(14, Suit::Spade),
+            card(7, Suit::Club),
+            card(14, Suit::Heart),
+            card(2, Suit::Diamond),
+            card(3, Suit::Club),
+            card(9, Suit::Spade),
+            card(10, Suit::Heart),
+            card(12, Suit::Diamond),
+            card(3, Suit::Diamond),
+            card(4, Suit::Heart),
+            card(3, Suit::Spade),
+            card(13, Suit::Club),
+        ])
+    }
+
+    #[test]
+    fn holdem_heads_up_blinds_and_order() {
+        let mut game = HoldemGame::new(&[100, 100], CONFIG).unwrap();
+        game.start_hand(heads_up_deck()).unwrap();
+
+        assert_eq!(game.button(), Some(0));
+        assert_eq!(game.seats()[0].street_bet(), 1);
+        assert_eq!(game.seats()[1].street_bet(), 2);
+        assert_eq!(game.to_act(), Some(0));
+        assert_eq!(game.seats()[1].hole_cards()[0].suit(), Suit::Spade);
+
+        game.act(Action::Call).unwrap();
+        assert_eq!(game.to_act(), Some(1));
+        game.act(Action::Check).unwrap();
+
+        assert_eq!(game.street(), Street::Flop);
+        assert_eq!(game.board().len(), 3);
+        assert_eq!(game.to_act(), Some(1));
+    }
+
+    #[test]
+    fn holdem_showdown() {
+        let mut game = HoldemGame::new(&[100, 100], CONFIG).unwrap();
+        game.start_hand(heads_up_deck()).unwrap();
+
+        game.act(Action::Raise(6)).unwrap();
+        game.act(Action::Call).unwrap();
+        for _ in 0..3 {
+            game.act(Action::Check).unwrap();
+            game.act(Action::Check).unwrap();
+        }
+
+        assert!(game.is_hand_over());
+        assert_eq!(game.board().len(), 5);
+        assert_eq!(game.winnings(), &[0, 12]);
+        assert_eq!(game.seats()[0].stack, 94);
+        assert_eq!(game.seats()[1].stack, 106);
+
+        // The button moves for the next hand.
+        game.start_hand(Deck::shuffled(1)).unwrap();
+        assert_eq!(game.button(), Some(1));
+    }
+
+    #[test]
+    fn holdem_fold_to_big_blind() {
+        let mut game = HoldemGame::new(&[100, 100, 100], CONFIG).unwrap();
+        game.start_hand(Deck::shuffled(7)).unwrap();
+
+        // Button is seat 0, so seat 1 is the small blind and seat 2 the big blind.
+        assert_eq!(game.to_act(), Some(0));
+        game.act(Action::Fold).unwrap();
+        game.act(Action::Fold).unwrap();
+
+        assert!(game.is_hand_over());
+        assert_eq!(game.winnings(), &[0, 0, 3]);
+        assert_eq!(game.seats()[2].stack, 101);
+    }
+
+    #[test]
+    fn holdem_illegal_actions() {
+        let mut game = HoldemGame::new(&[100, 100, 100], CONFIG).unwrap();
+        game.start_hand(Deck::shuffled(7)).unwrap();
+
+        let cases = vec![
+            (
+                "Check facing the big blind",
+                Action::Check,
+                ActionError::CannotCheck { to_call: 2 },
+            ),
+            (
+                "Bet facing the big blind",
+                Action::Bet(10),
+                ActionError::CannotBetFacingBet { current_bet: 2 },
+            ),
+            (
+                "Raise below the minimum",
+                Action::Raise(3),
+                ActionError::BelowMinimum { amount: 3, min: 4 },
+            ),
+            (
+                "Raise more than the stack",
+                Action::Raise(101),
+                ActionError::AboveMaximum {
+                    amount: 101,
+                    max: 100,
+                },
+            ),
+        ];
+        for (name, action, expected) in cases {
+            assert_eq!(game.act(action), Err(expected), "Case: {name}");
+        }
+
+        game.act(Action::Raise(6)).unwrap();
+        assert_eq!(
+            game.legal_actions(),
+            vec![
+                LegalAction::Fold,
+                LegalAction::Call(5),
+                LegalAction::Raise { min: 10, max: 100 },
+            ]
+        );
+    }
+
+    #[test]
+    fn holdem_all_in_runs_out_the_board() {
+        let mut game = HoldemGame::new(&[50, 100], CONFIG).unwrap();
+        game.start_hand(heads_up_deck()).unwrap();
+
+        game.act(Action::Raise(50)).unwrap();
+        game.act(Action::Call).unwrap();
+
+        assert!(game.is_hand_over());
+        assert_eq!(game.board().len(), 5);
+        assert_eq!(game.seats()[0].stack, 0);
+        assert_eq!(game.seats()[1].stack, 150);
+        assert!(game.start_hand(Deck::new()).is_err());
+    }
+
+    #[test]
+    fn holdem_fixed_limit() {
+        let fixed_limit = FixedLimit {
+            small_bet: 2,
+            big_bet: 4,
+            cap: 3,
+        };
+        let mut game =
+            HoldemGame::with_betting_structure(&[100, 100], CONFIG, fixed_limit).unwrap();
+        game.start_hand(heads_up_deck()).unwrap();
+
+        // The big blind counts as the first bet, so two raises cap it.
+        game.act(Action::Raise(4)).unwrap();
+        game.act(Action::Raise(6)).unwrap();
+        assert_eq!(
+            game.act(Action::Raise(8)),
+            Err(ActionError::BettingCapped { bets: 3 })
+        );
+        game.act(Action::Call).unwrap();
+
+        // Flop is the small bet, turn the big bet.
+        assert_eq!(
+            game.act(Action::Bet(4)),
+            Err(ActionError::AboveMaximum { amount: 4, max: 2 })
+        );
+        game.act(Action::Bet(2)).unwrap();
+        game.act(Action::Call).unwrap();
+        assert_eq!(
+            game.legal_actions(),
+            vec![
+                LegalAction::Fold,
+                LegalAction::Check,
+                LegalAction::Bet { min: 4, max: 4 },
+            ]
+        );
+    }
+
+    #[test]
+    fn holdem_side_pot() {
+        // Seat 1 has aces but is all in for less, seat 2 beats seat 0 for the side pot.
+        let deck = Deck::stacked(&[
+            card(14, Suit::Spade),
+            card(13, Suit::Spade),
+            card(2, Suit::Club),
+            card(14, Suit::Heart),
+            card(13, Suit::Heart),
+            card(7, Suit::Diamond),
+            card(3, Suit::Club),
+            card(9, Suit::Spade),
+            card(10, Suit::Heart),
+            card(12, Suit::Diamond),
+            card(3, Suit::Diamond),
+            card(4, Suit::Heart),
+            card(3, Suit::Spade),
+            card(5, Suit::Club),
+        ]);
+        let mut game = HoldemGame::new(&[100, 30, 100], CONFIG).unwrap();
+        game.start_hand(deck).unwrap();
+
+        // Button is seat 0, small blind seat 1, big blind seat 2.
+        game.act(Action::Raise(100)).unwrap();
+        game.act(Action::Call).unwrap();
+        game.act(Action::Call).unwrap();
+
+        assert!(game.is_hand_over());
+        assert_eq!(game.winnings(), &[0, 90, 140]);
+    }
+}
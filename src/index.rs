@@ -0,0 +1,284 @@
+use std::sync::OnceLock;
+
+use crate::Card;
+use crate::deck::SUITS;
+use crate::encoding;
+
+pub const FIVE_CARD_HANDS: u32 = 2_598_960;
+pub const SEVEN_CARD_HANDS: u32 = 133_784_560;
+
+/// Position of a five or seven card hand among all hands of its size, from 0 to
+/// `FIVE_CARD_HANDS - 1` or `SEVEN_CARD_HANDS - 1`.
+///
+/// `Card`'s `==` and `Ord` only look at ranks, so this goes by the card numbers of
+/// `encoding::card_to_bits`, which tell suits apart.
+pub fn index_of(hand: &[Card]) -> Result<u32, String> {
+    check_size(hand.len())?;
+    encoding::combination_index(hand)
+}
+
+/// The hand of `cards` cards at `index`, sorted by card number. Undoes `index_of`.
+pub fn hand_at(index: u32, cards: usize) -> Result<Vec<Card>, String> {
+    check_size(cards)?;
+    encoding::from_combination_index(index, cards)
+}
+
+fn check_size(cards: usize) -> Result<(), String> {
+    match cards {
+        5 | 7 => Ok(()),
+        _ => Err(format!("Hands must have 5 or 7 cards, not {cards}")),
+    }
+}
+
+/// The same hand with its suits renamed so that every hand that only differs by a
+/// permutation of the suits comes out the same. Suits are handed out by the ranks they hold:
+/// the suit with the highest rank mask becomes clubs, then diamonds, hearts and spades.
+pub fn canonical(hand: &[Card]) -> Result<Vec<Card>, String> {
+    let masks = suit_masks(hand)?;
+    Ok(cards_of(&masks))
+}
+
+/// Index of `canonical(hand)` among the canonical hands of that size, from 0 to
+/// `canonical_count(hand.len()) - 1`.
+pub fn canonical_index_of(hand: &[Card]) -> Result<u32, String> {
+    check_size(hand.len())?;
+    let masks = suit_masks(hand)?;
+    let table = table();
+
+    let mut index = 0;
+    let mut left = hand.len();
+    for (i, &mask) in masks.iter().enumerate() {
+        // Every canonical hand that matches so far and has a smaller mask for this suit.
+        index += table.count(4 - i, left, mask as i32 - 1);
+        left -= mask.count_ones() as usize;
+    }
+
+    Ok(index)
+}
+
+/// The canonical hand at `index`. Undoes `canonical_index_of`.
+pub fn canonical_hand_at(index: u32, cards: usize) -> Result<Vec<Card>, String> {
+    check_size(cards)?;
+    if index >= canonical_count(cards) {
+        return Err(format!(
+            "{index} is past the last canonical hand of {cards} cards"
+        ));
+    }
+
+    let table = table();
+    let mut rest = index;
+    let mut left = cards;
+    let mut masks = [0; 4];
+    let mut largest = RANK_MASKS as i32 - 1;
+    for (i, mask) in masks.iter_mut().enumerate() {
+        // The largest mask whose smaller masks do not use up what is left of the index.
+        let row = &table.row(4 - i, left)[..=largest as usize];
+        let found = row.partition_point(|&count| count <= rest) - 1;
+        rest -= row[found];
+        *mask = found as u16;
+        largest = found as i32;
+        left -= mask.count_ones() as usize;
+    }
+
+    Ok(cards_of(&masks))
+}
+
+/// How many hands of `cards` cards are left once hands that only differ by suits are merged:
+/// 134,459 for five cards and 6,009,159 for seven.
+pub fn canonical_count(cards: usize) -> u32 {
+    assert!(
+        cards <= MAX_CARDS,
+        "Only hands of up to {MAX_CARDS} cards are counted"
+    );
+    table().count(4, cards, RANK_MASKS as i32 - 1)
+}
+
+const RANK_MASKS: usize = 1 << 13;
+
+// One 13 bit rank mask per suit, largest first.
+fn suit_masks(hand: &[Card]) -> Result<[u16; 4], String> {
+    encoding::mask(hand)?;
+
+    let mut masks = [0u16; 4];
+    for card in hand {
+        let suit = SUITS
+            .iter()
+            .position(|suit| *suit == card.suit)
+            .expect("SUITS holds every suit");
+        masks[suit] |= 1 << (card.rank - 2);
+    }
+    masks.sort_unstable_by(|a, b| b.cmp(a));
+
+    Ok(masks)
+}
+
+fn cards_of(masks: &[u16; 4]) -> Vec<Card> {
+    let mut cards = Vec::new();
+    for (suit, mask) in SUITS.iter().zip(masks) {
+        for rank in 2..=14 {
+            if mask & (1 << (rank - 2)) != 0 {
+                cards.push(Card { rank, suit: *suit });
+            }
+        }
+    }
+    cards
+}
+
+// `counts[suits][cards][mask + 1]` is how many ways there are to give `suits` suits rank masks
+// that are at most `mask`, never go up from one suit to the next, and hold `cards` cards in
+// total. Those are exactly the canonical hands.
+struct Table {
+    counts: Vec<u32>,
+}
+
+const MAX_CARDS: usize = encoding::MAX_INDEXED_CARDS;
+
+impl Table {
+    fn build() -> Self {
+        let mut table = Table {
+            counts: vec![0; 5 * (MAX_CARDS + 1) * (RANK_MASKS + 1)],
+        };
+
+        for suits in 0..=4 {
+            for cards in 0..=MAX_CARDS {
+                for mask in 0..RANK_MASKS {
+                    let count = if suits == 0 {
+                        (cards == 0) as u32
+                    } else {
+                        let used = (mask as u16).count_ones() as usize;
+                        let with_mask = if used <= cards {
+                            table.count(suits - 1, cards - used, mask as i32)
+                        } else {
+                            0
+                        };
+                        table.count(suits, cards, mask as i32 - 1) + with_mask
+                    };
+                    let at = table.at(suits, cards, mask as i32);
+                    table.counts[at] = count;
+                }
+            }
+        }
+
+        table
+    }
+
+    fn at(&self, suits: usize, cards: usize, mask: i32) -> usize {
+        (suits * (MAX_CARDS + 1) + cards) * (RANK_MASKS + 1) + (mask + 1) as usize
+    }
+
+    fn count(&self, suits: usize, cards: usize, mask: i32) -> u32 {
+        self.counts[self.at(suits, cards, mask)]
+    }
+
+    fn row(&self, suits: usize, cards: usize) -> &[u32] {
+        let start = self.at(suits, cards, -1);
+        &self.counts[start..start + RANK_MASKS + 1]
+    }
+}
+
+fn table() -> &'static Table {
+    static TABLE: OnceLock<Table> = OnceLock::new();
+    TABLE.get_or_init(Table::build)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::{Deck, Rng};
+    use crate::encoding::binomial;
+    use crate::{PokerHand, Suit};
+
+    fn same_cards(a: &[Card], b: &[Card]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.is_same_card(b))
+    }
+
+    fn random_hand(rng: &mut Rng, cards: usize) -> Vec<Card> {
+        let mut deck = Deck::new();
+        deck.shuffle(rng);
+        (0..cards).map(|_| deck.deal().unwrap()).collect()
+    }
+
+    #[test]
+    fn index_bijection() {
+        assert_eq!(binomial(52, 5), FIVE_CARD_HANDS as u64);
+        assert_eq!(binomial(52, 7), SEVEN_CARD_HANDS as u64);
+
+        for (cards, total) in [(5, FIVE_CARD_HANDS), (7, SEVEN_CARD_HANDS)] {
+            for index in (0..total).step_by(total as usize / 5000).chain([total - 1]) {
+                assert_eq!(index_of(&hand_at(index, cards).unwrap()).unwrap(), index);
+            }
+            assert!(hand_at(total, cards).is_err());
+        }
+
+        let mut rng = Rng::new(36);
+        for cards in [5, 7] {
+            for _ in 0..500 {
+                let hand = random_hand(&mut rng, cards);
+                let back = hand_at(index_of(&hand).unwrap(), cards).unwrap();
+                assert_eq!(encoding::mask(&back), encoding::mask(&hand));
+            }
+        }
+
+        assert!(index_of(&random_hand(&mut rng, 6)).is_err());
+        assert!(hand_at(0, 2).is_err());
+    }
+
+    #[test]
+    fn index_canonical_counts() {
+        assert_eq!(canonical_count(5), 134_459);
+        assert_eq!(canonical_count(7), 6_009_159);
+
+        for (cards, step) in [(5, 1), (7, 997)] {
+            let total = canonical_count(cards);
+            for index in (0..total).step_by(step).chain([total - 1]) {
+                let hand = canonical_hand_at(index, cards).unwrap();
+                assert!(same_cards(&canonical(&hand).unwrap(), &hand));
+                assert_eq!(canonical_index_of(&hand).unwrap(), index);
+            }
+            assert!(canonical_hand_at(total, cards).is_err());
+        }
+    }
+
+    #[test]
+    fn index_canonical_ignores_suit_permutations() {
+        let mut rng = Rng::new(7);
+
+        for cards in [5, 7] {
+            for _ in 0..200 {
+                let hand = random_hand(&mut rng, cards);
+                let index = canonical_index_of(&hand).unwrap();
+                let best = PokerHand::best_of(&hand).unwrap();
+
+                // Swap suits around in a few different ways; the canonical index stays put.
+                for shift in 1..4 {
+                    let renamed: Vec<Card> = hand
+                        .iter()
+                        .map(|card| {
+                            let suit = SUITS.iter().position(|suit| *suit == card.suit).unwrap();
+                            Card::new(card.rank, SUITS[(suit * 3 + shift) % 4]).unwrap()
+                        })
+                        .collect();
+                    assert_eq!(canonical_index_of(&renamed).unwrap(), index);
+                }
+
+                let canonical = canonical_hand_at(index, cards).unwrap();
+                let canonical_best = PokerHand::best_of(&canonical).unwrap();
+                assert_eq!(canonical_best.poker_hand_type, best.poker_hand_type);
+            }
+        }
+
+        // Different suit patterns are told apart.
+        let flush = crate::parse_cards("2h 5h 9h Jh Kh").unwrap();
+        let no_flush = crate::parse_cards("2h 5h 9h Jh Kd").unwrap();
+        assert_ne!(
+            canonical_index_of(&flush).unwrap(),
+            canonical_index_of(&no_flush).unwrap()
+        );
+        assert!(
+            canonical(&flush)
+                .unwrap()
+                .iter()
+                .all(|card| card.suit == Suit::Club)
+        );
+    }
+}
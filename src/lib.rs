@@ -1,16 +1,111 @@
-use std::cmp::Ordering;
-use std::collections::HashMap;
-use std::fmt;
+#![cfg_attr(not(feature = "std"), no_std)]
 
-#[derive(Debug, PartialEq)]
-enum Suit {
+use core::cmp::Ordering;
+use core::fmt;
+#[cfg(feature = "std")]
+use core::str::FromStr;
+
+use eval::HandBuilder;
+
+#[cfg(feature = "std")]
+pub mod betting;
+#[cfg(feature = "std")]
+pub mod deck;
+#[cfg(feature = "std")]
+pub mod encoding;
+#[cfg(feature = "std")]
+pub mod equity;
+pub mod eval;
+#[cfg(feature = "std")]
+pub mod history;
+#[cfg(feature = "std")]
+pub mod holdem;
+#[cfg(feature = "std")]
+pub mod index;
+#[cfg(feature = "std")]
+pub mod ofc;
+#[cfg(feature = "std")]
+pub mod outs;
+#[cfg(feature = "std")]
+pub mod partial;
+#[cfg(feature = "std")]
+pub mod phh;
+#[cfg(feature = "std")]
+pub mod pokerstars;
+#[cfg(feature = "std")]
+pub mod pot;
+#[cfg(feature = "std")]
+pub mod preflop;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "std")]
+pub mod simulation;
+#[cfg(feature = "std")]
+pub mod sort;
+#[cfg(feature = "std")]
+pub mod stud;
+#[cfg(feature = "std")]
+pub mod video_poker;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Suit {
     Heart,
     Club,
     Spade,
     Diamond,
 }
 
-struct Card {
+/// The order suits are ranked in when a game lets suits break ties. `Suit` itself is ordered
+/// alphabetically: clubs < diamonds < hearts < spades.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SuitOrder([Suit; 4]);
+
+impl SuitOrder {
+    pub const ALPHABETICAL: SuitOrder =
+        SuitOrder([Suit::Club, Suit::Diamond, Suit::Heart, Suit::Spade]);
+
+    /// Bridge ranks the suits clubs < diamonds < hearts < spades, which happens to be alphabetical.
+    pub const BRIDGE: SuitOrder = SuitOrder::ALPHABETICAL;
+
+    /// Builds an order from the lowest suit to the highest. Every suit must show up exactly once.
+    pub fn custom(order: [Suit; 4]) -> Result<Self, &'static str> {
+        for (i, suit) in order.iter().enumerate() {
+            if order[i + 1..].contains(suit) {
+                return Err("Each suit must show up exactly once in a suit order");
+            }
+        }
+
+        Ok(SuitOrder(order))
+    }
+
+    /// Position of the suit in the order, 0 being the lowest.
+    pub fn rank(&self, suit: Suit) -> u8 {
+        self.0
+            .iter()
+            .position(|s| *s == suit)
+            .expect("SuitOrder holds every suit") as u8
+    }
+
+    pub fn compare(&self, a: Suit, b: Suit) -> Ordering {
+        self.rank(a).cmp(&self.rank(b))
+    }
+}
+
+impl PartialOrd for Suit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Suit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        SuitOrder::ALPHABETICAL.compare(*self, *other)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Card {
     rank: u8,
     suit: Suit,
 }
@@ -18,12 +113,12 @@ struct Card {
 impl fmt::Debug for Card {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let rank = match self.rank {
-            x @ 2..=10 => x.to_string(),
-            11 => "J".to_string(),
-            12 => "Q".to_string(),
-            13 => "K".to_string(),
-            14 => "A".to_string(),
-            _ => panic!("number {:?} is not a valid card number", &self.rank),
+            2..=10 => ["2", "3", "4", "5", "6", "7", "8", "9", "10"][self.rank as usize - 2],
+            11 => "J",
+            12 => "Q",
+            13 => "K",
+            14 => "A",
+            _ => panic!("number {:?} is not a valid card number", self.rank),
         };
 
         f.debug_struct("Card")
@@ -34,12 +129,104 @@ impl fmt::Debug for Card {
 }
 
 impl Card {
-    fn new(rank: u8, suit: Suit) -> Result<Self, &'static str> {
+    pub const fn new(rank: u8, suit: Suit) -> Result<Self, &'static str> {
         match rank {
             x @ 2..=14 => Ok(Card { rank: x, suit }),
             _ => Err("name must be in the range of 2 - 14"),
         }
     }
+
+    pub const fn rank(&self) -> u8 {
+        self.rank
+    }
+
+    pub const fn suit(&self) -> Suit {
+        self.suit
+    }
+
+    /// `==` only compares ranks, this also compares the suits.
+    pub fn is_same_card(&self, other: &Card) -> bool {
+        self.rank == other.rank && self.suit == other.suit
+    }
+}
+
+/// Short form used by hand histories: rank then suit, e.g. `Ah`, `Td`, `2c`.
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rank = match self.rank {
+            x @ 2..=9 => (b'0' + x) as char,
+            10 => 'T',
+            11 => 'J',
+            12 => 'Q',
+            13 => 'K',
+            _ => 'A',
+        };
+        let suit = match self.suit {
+            Suit::Club => 'c',
+            Suit::Diamond => 'd',
+            Suit::Heart => 'h',
+            Suit::Spade => 's',
+        };
+
+        write!(f, "{rank}{suit}")
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromStr for Card {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rank, suit) = s
+            .char_indices()
+            .last()
+            .map(|(i, suit)| (&s[..i], suit))
+            .ok_or_else(|| "empty card".to_string())?;
+
+        let rank = match rank.to_ascii_uppercase().as_str() {
+            "T" | "10" => 10,
+            "J" => 11,
+            "Q" => 12,
+            "K" => 13,
+            "A" => 14,
+            x => match x.parse::<u8>() {
+                Ok(rank @ 2..=9) => rank,
+                _ => return Err(format!("{s:?} has an unknown rank")),
+            },
+        };
+        let suit = match suit.to_ascii_lowercase() {
+            'c' => Suit::Club,
+            'd' => Suit::Diamond,
+            'h' => Suit::Heart,
+            's' => Suit::Spade,
+            _ => return Err(format!("{s:?} has an unknown suit")),
+        };
+
+        Ok(Card { rank, suit })
+    }
+}
+
+/// Parses cards written like `"Ah Kh"`, `"AhKh"` or `"Ah,Kh"`.
+#[cfg(feature = "std")]
+pub fn parse_cards(s: &str) -> Result<Vec<Card>, String> {
+    let compact: String = s
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ',')
+        .collect();
+
+    let mut cards = Vec::new();
+    let mut start = 0;
+    for (i, c) in compact.char_indices() {
+        if "cdhsCDHS".contains(c) && i > start {
+            cards.push(compact[start..=i].parse()?);
+            start = i + 1;
+        }
+    }
+    if start != compact.len() {
+        return Err(format!("{:?} is not a card", &compact[start..]));
+    }
+
+    Ok(cards)
 }
 
 impl PartialEq for Card {
@@ -52,7 +239,7 @@ impl Eq for Card {}
 
 impl PartialOrd for Card {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(&other))
+        Some(self.cmp(other))
     }
 }
 
@@ -68,7 +255,8 @@ When I compare two hands, I want the greater hand to return Ordering::Greater.
 To make sure that happens, I needed to put the "greater" poker hand type at the bottom.
 */
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Clone)]
-enum PokerHandType {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PokerHandType {
     HighCard,
     OnePair,
     TwoPair,
@@ -81,15 +269,34 @@ enum PokerHandType {
     RoyalFlush,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-struct PokerHand {
-    cards: Vec<Card>,
+impl fmt::Display for PokerHandType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PokerHandType::HighCard => "High card",
+            PokerHandType::OnePair => "One pair",
+            PokerHandType::TwoPair => "Two pair",
+            PokerHandType::ThreeOfAKind => "Three of a kind",
+            PokerHandType::Straight => "Straight",
+            PokerHandType::Flush => "Flush",
+            PokerHandType::FullHouse => "Full house",
+            PokerHandType::FourOfAKind => "Four of a kind",
+            PokerHandType::StraightFlush => "Straight flush",
+            PokerHandType::RoyalFlush => "Royal flush",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PokerHand {
+    cards: HandBuilder,
     poker_hand_type: Option<PokerHandType>,
 }
 
 impl PartialOrd for PokerHand {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(&other))
+        Some(self.cmp(other))
     }
 }
 
@@ -110,206 +317,210 @@ impl Ord for PokerHand {
                     .clone()
                     .expect("Must call sort_hand_before sorting"),
             )
-            .then(self.cards.cmp(&other.cards).reverse())
+            .then(self.cards().cmp(other.cards()).reverse())
+    }
+}
+
+impl Default for PokerHand {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl PokerHand {
-    fn new() -> Self {
+    pub fn new() -> Self {
         PokerHand {
-            cards: Vec::new(),
+            cards: HandBuilder::new(),
             poker_hand_type: None,
         }
     }
 
-    fn add_card(&mut self, card: Card) -> Result<(), &'static str> {
-        // Our card hand limit is 5.
-        if self.cards.len() == 5 {
-            return Err("Max hand limit is 5");
-        }
-
-        self.cards.push(card);
-        Ok(())
+    pub fn cards(&self) -> &[Card] {
+        self.cards.cards()
     }
 
-    fn card_rank_histogram(&self) -> Vec<(u8, usize)> {
-        let mut map = HashMap::new();
+    /// `None` until the hand was sorted or `set_hand_type` was called.
+    pub fn poker_hand_type(&self) -> Option<&PokerHandType> {
+        self.poker_hand_type.as_ref()
+    }
 
-        for card in &self.cards {
-            map.entry(card.rank)
-                .and_modify(|count| *count += 1)
-                .or_insert(1);
-        }
+    /// What the hand is in words, e.g. `Full house, Kings full of Threes`. Needs a sorted hand.
+    #[cfg(feature = "std")]
+    pub fn description(&self) -> Option<String> {
+        let hand_type = self.poker_hand_type.as_ref()?;
+        let rank = |i: usize| rank_name(self.cards()[i].rank);
+        let plural = |i: usize| rank_plural(self.cards()[i].rank);
 
-        let mut results: Vec<(u8, usize)> = map
-            .iter()
-            .map(|(k, v)| (k.clone(), v.clone() as usize))
-            .collect();
-        results.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(&b.0)).reverse());
+        Some(match hand_type {
+            PokerHandType::HighCard => format!("High card {}", rank(0)),
+            PokerHandType::OnePair => format!("Pair of {}", plural(0)),
+            PokerHandType::TwoPair => format!("Two pair, {} and {}", plural(0), plural(2)),
+            PokerHandType::ThreeOfAKind => format!("Three of a kind, {}", plural(0)),
+            PokerHandType::Straight => format!("Straight, {} high", rank(0)),
+            PokerHandType::Flush => format!("Flush, {} high", rank(0)),
+            PokerHandType::FullHouse => {
+                format!("Full house, {} full of {}", plural(0), plural(3))
+            }
+            PokerHandType::FourOfAKind => format!("Four of a kind, {}", plural(0)),
+            PokerHandType::StraightFlush => format!("Straight flush, {} high", rank(0)),
+            PokerHandType::RoyalFlush => "Royal flush".to_string(),
+        })
+    }
 
-        results
+    pub fn add_card(&mut self, card: Card) -> Result<(), &'static str> {
+        // Our card hand limit is 5.
+        self.cards.add_card(card)
     }
 
-    fn have_flush(&self) -> bool {
-        let mut result = true;
+    /// Compares two sorted hands like `cmp`, but when they tie the suits decide, card by card
+    /// starting from the most important one. Without a `suit_order` this is the same as `cmp`.
+    pub fn cmp_with_tie_break(&self, other: &Self, suit_order: Option<SuitOrder>) -> Ordering {
+        let ordering = self.cmp(other);
 
-        let suit = &self.cards[0].suit;
-        for card in &self.cards[1..] {
-            if card.suit != *suit {
-                result = false;
-                break;
-            }
+        match suit_order {
+            Some(suit_order) if ordering == Ordering::Equal => self
+                .cards()
+                .iter()
+                .zip(other.cards())
+                .map(|(a, b)| suit_order.compare(a.suit, b.suit))
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal),
+            _ => ordering,
         }
-
-        result
     }
 
-    fn have_straight(&self) -> bool {
-        let mut ranks: Vec<u8> = self.cards.iter().map(|card| card.rank.clone()).collect();
-
-        ranks.sort();
+    /// Picks the strongest five card hand out of `cards` (e.g. the seven cards of stud or hold'em).
+    /// The returned hand has already been sorted.
+    pub fn best_of(cards: &[Card]) -> Result<PokerHand, &'static str> {
+        if cards.len() < 5 {
+            return Err("Must have at least 5 Cards to pick the best hand");
+        }
 
-        if ranks == [2, 3, 4, 5, 14] {
-            true
-        } else {
-            let mut result = true;
+        eval::best_of(cards)
+            .map(PokerHand::from)
+            .ok_or("Unable to pick the best hand")
+    }
 
-            let mut expected = ranks[0];
-            for num in ranks {
-                if num != expected {
-                    result = false;
-                    break;
-                }
+    pub fn set_hand_type(&mut self) -> Result<(), &'static str> {
+        let value = self.evaluate()?;
+        self.poker_hand_type = Some(value.hand_type().clone());
+        Ok(())
+    }
 
-                expected += 1
-            }
+    pub fn sort_hand(&mut self) -> Result<(), &'static str> {
+        *self = PokerHand::from(self.evaluate()?);
+        Ok(())
+    }
 
-            result
+    /// The hand's type and sorted cards, without touching the hand and without allocating.
+    pub fn evaluate(&self) -> Result<eval::HandValue, &'static str> {
+        match self.cards.build() {
+            Ok(cards) => Ok(eval::evaluate(&cards)),
+            Err(_) => Err("Must have 5 Cards to set hand type"),
         }
     }
+}
 
-    fn set_hand_type(&mut self) -> Result<(), &'static str> {
-        if self.cards.len() != 5 {
-            return Err("Must have 5 Cards to set hand type");
+/// The suit most of `cards` share and how many share it, `None` without cards. Works for any
+/// number of cards, e.g. to spot a four card flush draw.
+#[cfg(feature = "std")]
+pub(crate) fn most_suited(cards: &[Card]) -> Option<(Suit, usize)> {
+    let mut counts: Vec<(Suit, usize)> = Vec::new();
+    for card in cards {
+        match counts.iter_mut().find(|(suit, _)| *suit == card.suit) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((card.suit, 1)),
         }
+    }
 
-        self.poker_hand_type = None;
-
-        let card_rank_histogram = self.card_rank_histogram();
-
-        // Check Poker hand Types that have multiple cards of the same rank
-        if card_rank_histogram[0].1 == 4 {
-            self.poker_hand_type = Some(PokerHandType::FourOfAKind);
-        } else if card_rank_histogram[0].1 == 3 && card_rank_histogram[1].1 == 2 {
-            self.poker_hand_type = Some(PokerHandType::FullHouse);
-        } else if card_rank_histogram[0].1 == 3 && card_rank_histogram[1].1 == 1 {
-            self.poker_hand_type = Some(PokerHandType::ThreeOfAKind);
-        } else if card_rank_histogram[0].1 == 2 && card_rank_histogram[1].1 == 2 {
-            self.poker_hand_type = Some(PokerHandType::TwoPair);
-        } else if card_rank_histogram[0].1 == 2 && card_rank_histogram[1].1 == 1 {
-            self.poker_hand_type = Some(PokerHandType::OnePair)
-        }
+    counts.into_iter().max_by_key(|(_, count)| *count)
+}
 
-        // Check to see if we should return early
-        if self.poker_hand_type.is_some() {
-            return Ok(());
+/// Bit `rank` set for every rank in `cards`, and bit 1 as well for an ace so it can play low.
+pub(crate) const fn rank_bits(cards: &[Card]) -> u16 {
+    let mut bits = 0;
+    let mut i = 0;
+    while i < cards.len() {
+        bits |= 1 << cards[i].rank;
+        if cards[i].rank == 14 {
+            bits |= 1 << 1;
         }
+        i += 1;
+    }
+    bits
+}
 
-        match (self.have_straight(), self.have_flush()) {
-            (false, false) => self.poker_hand_type = Some(PokerHandType::HighCard),
-            (false, true) => self.poker_hand_type = Some(PokerHandType::Flush),
-            (true, false) => self.poker_hand_type = Some(PokerHandType::Straight),
-            (true, true) => {
-                let mut ranks: Vec<u8> = self.cards.iter().map(|card| card.rank.clone()).collect();
-                ranks.sort();
-                if ranks[0] == 10 {
-                    self.poker_hand_type = Some(PokerHandType::RoyalFlush);
-                } else {
-                    self.poker_hand_type = Some(PokerHandType::StraightFlush);
-                }
-            }
+/// The high card of the best straight in `bits` (from `rank_bits`), if there is one.
+pub(crate) const fn straight_high(bits: u16) -> Option<u8> {
+    let mut high = 14;
+    while high >= 5 {
+        let run = 0b11111 << (high - 4);
+        if bits & run == run {
+            return Some(high);
         }
+        high -= 1;
+    }
+    None
+}
 
-        match self.poker_hand_type {
-            None => Err("We unable to figure out your poker hand type"),
-            Some(ref _hand_type) => Ok(()),
-        }
+/// Ranks that would each give `bits` a straight, or a higher one than it has: one rank for a
+/// gutshot, two for an open-ended draw. Aces come back as 14.
+#[cfg(feature = "std")]
+pub(crate) fn straight_draw_ranks(bits: u16) -> Vec<u8> {
+    (2..=14)
+        .filter(|&rank| bits & 1 << rank == 0)
+        .filter(|&rank| {
+            let with = bits | 1 << rank | if rank == 14 { 1 << 1 } else { 0 };
+            straight_high(with).is_some_and(|high| Some(high) > straight_high(bits))
+        })
+        .collect()
+}
+
+#[cfg(feature = "std")]
+fn rank_name(rank: u8) -> &'static str {
+    [
+        "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine", "Ten", "Jack", "Queen",
+        "King", "Ace",
+    ][rank as usize - 2]
+}
+
+#[cfg(feature = "std")]
+fn rank_plural(rank: u8) -> String {
+    match rank {
+        6 => "Sixes".to_string(),
+        _ => format!("{}s", rank_name(rank)),
     }
+}
 
-    fn sort_hand(&mut self) -> Result<(), String> {
-        let _ = self.set_hand_type()?;
-        let poker_hand_type = self.poker_hand_type.clone().unwrap();
-        let card_rank_histogram = self.card_rank_histogram();
-
-        match poker_hand_type {
-            PokerHandType::OnePair | PokerHandType::ThreeOfAKind | PokerHandType::FourOfAKind => {
-                let priority_card_rank = card_rank_histogram[0].0;
-
-                self.cards.sort_by(|a, b| {
-                    if a.rank == priority_card_rank && b.rank == priority_card_rank {
-                        Ordering::Equal
-                    } else if a.rank == priority_card_rank && b.rank != priority_card_rank {
-                        Ordering::Less
-                    } else if a.rank != priority_card_rank && b.rank == priority_card_rank {
-                        Ordering::Greater
-                    } else {
-                        a.cmp(&b)
-                    }
-                });
-
-                Ok(())
-            }
-            PokerHandType::RoyalFlush
-            | PokerHandType::StraightFlush
-            | PokerHandType::Flush
-            | PokerHandType::Straight
-            | PokerHandType::HighCard => {
-                self.cards.sort();
-
-                if poker_hand_type == PokerHandType::StraightFlush
-                    || poker_hand_type == PokerHandType::Straight
-                {
-                    // In the case where the straight is Ace, 5, 4 ,3, 2, 1, we need to list
-                    // the left by 1 -> 5, 4, 3, 2, Ace
-                    if self.cards[0].rank == 14 && self.cards[1].rank == 5 {
-                        self.cards.rotate_left(1);
-                    }
-                }
-                Ok(())
-            }
-            PokerHandType::FullHouse | PokerHandType::TwoPair => {
-                let priority_1 = card_rank_histogram[0].0;
-                let priority_2 = card_rank_histogram[1].0;
-
-                self.cards.sort_by(|a, b| {
-                    if a.rank == priority_1 && b.rank == priority_1 {
-                        a.cmp(&b)
-                    } else if a.rank == priority_1 && b.rank == priority_2 {
-                        Ordering::Less
-                    } else if a.rank == priority_2 && b.rank == priority_1 {
-                        Ordering::Greater
-                    } else if a.rank == priority_2 && b.rank == priority_2 {
-                        a.cmp(&b)
-                    } else if a.rank == priority_1 && b.rank != priority_1 && b.rank != priority_2 {
-                        Ordering::Less
-                    } else if a.rank == priority_2 && b.rank != priority_1 && b.rank != priority_2 {
-                        Ordering::Less
-                    } else if a.rank != priority_1 && a.rank != priority_2 && b.rank == priority_1 {
-                        Ordering::Greater
-                    } else if a.rank != priority_1 && a.rank != priority_2 && b.rank == priority_2 {
-                        Ordering::Greater
-                    } else {
-                        a.cmp(&b)
-                    }
-                });
-
-                Ok(())
-            }
+/// Every way to pick `k` indexes out of `0..n`, in lexicographic order.
+#[cfg(feature = "std")]
+pub(crate) fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut results = Vec::new();
+    if k > n {
+        return results;
+    }
+
+    let mut indexes: Vec<usize> = (0..k).collect();
+    loop {
+        results.push(indexes.clone());
+
+        // Find the right most index that can still move to the right.
+        let mut i = k;
+        while i > 0 && indexes[i - 1] == n - k + i - 1 {
+            i -= 1;
+        }
+        if i == 0 {
+            return results;
+        }
+
+        indexes[i - 1] += 1;
+        for j in i..k {
+            indexes[j] = indexes[j - 1] + 1;
         }
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
 
@@ -320,23 +531,23 @@ mod tests {
                 "Same hand different Suit -- RoyalFlush",
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::RoyalFlush),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(14, Suit::Club).unwrap(),
                         Card::new(13, Suit::Club).unwrap(),
                         Card::new(12, Suit::Club).unwrap(),
                         Card::new(11, Suit::Club).unwrap(),
                         Card::new(10, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::RoyalFlush),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(14, Suit::Heart).unwrap(),
                         Card::new(13, Suit::Heart).unwrap(),
                         Card::new(12, Suit::Heart).unwrap(),
                         Card::new(11, Suit::Heart).unwrap(),
                         Card::new(10, Suit::Heart).unwrap(),
-                    ],
+                    ]),
                 },
                 Ordering::Equal,
             ),
@@ -344,23 +555,23 @@ mod tests {
                 "PokerHandType gets compared first -- FourOfAKind > HighCard",
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::FourOfAKind),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(2, Suit::Club).unwrap(),
                         Card::new(2, Suit::Heart).unwrap(),
                         Card::new(2, Suit::Spade).unwrap(),
                         Card::new(2, Suit::Club).unwrap(),
                         Card::new(10, Suit::Diamond).unwrap(),
-                    ],
+                    ]),
                 },
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::HighCard),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(14, Suit::Club).unwrap(),
                         Card::new(11, Suit::Club).unwrap(),
                         Card::new(8, Suit::Diamond).unwrap(),
                         Card::new(5, Suit::Club).unwrap(),
                         Card::new(2, Suit::Heart).unwrap(),
-                    ],
+                    ]),
                 },
                 Ordering::Greater,
             ),
@@ -368,23 +579,23 @@ mod tests {
                 "Same PokerHandType, but the hands are not equal",
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::FourOfAKind),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(2, Suit::Club).unwrap(),
                         Card::new(2, Suit::Heart).unwrap(),
                         Card::new(2, Suit::Spade).unwrap(),
                         Card::new(2, Suit::Club).unwrap(),
                         Card::new(10, Suit::Diamond).unwrap(),
-                    ],
+                    ]),
                 },
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::FourOfAKind),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(2, Suit::Club).unwrap(),
                         Card::new(2, Suit::Heart).unwrap(),
                         Card::new(2, Suit::Spade).unwrap(),
                         Card::new(2, Suit::Club).unwrap(),
                         Card::new(11, Suit::Diamond).unwrap(),
-                    ],
+                    ]),
                 },
                 Ordering::Less,
             ),
@@ -392,23 +603,23 @@ mod tests {
                 "Extra case for Straights when the Ace counts as 1",
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::Straight),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(5, Suit::Club).unwrap(),
                         Card::new(4, Suit::Club).unwrap(),
                         Card::new(3, Suit::Diamond).unwrap(),
                         Card::new(2, Suit::Heart).unwrap(),
                         Card::new(14, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::Straight),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(6, Suit::Club).unwrap(),
                         Card::new(5, Suit::Club).unwrap(),
                         Card::new(4, Suit::Diamond).unwrap(),
                         Card::new(3, Suit::Heart).unwrap(),
                         Card::new(2, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
                 Ordering::Less,
             ),
@@ -426,230 +637,230 @@ mod tests {
                 "One Pair",
                 PokerHand {
                     poker_hand_type: None,
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(2, Suit::Club).unwrap(),
                         Card::new(10, Suit::Diamond).unwrap(),
                         Card::new(14, Suit::Spade).unwrap(),
                         Card::new(2, Suit::Heart).unwrap(),
                         Card::new(9, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::OnePair),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(2, Suit::Club).unwrap(),
                         Card::new(2, Suit::Heart).unwrap(),
                         Card::new(14, Suit::Spade).unwrap(),
                         Card::new(10, Suit::Diamond).unwrap(),
                         Card::new(9, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
             ),
             (
                 "Three of a Kind",
                 PokerHand {
                     poker_hand_type: None,
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(2, Suit::Club).unwrap(),
                         Card::new(10, Suit::Diamond).unwrap(),
                         Card::new(2, Suit::Spade).unwrap(),
                         Card::new(2, Suit::Heart).unwrap(),
                         Card::new(9, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::ThreeOfAKind),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(2, Suit::Club).unwrap(),
                         Card::new(2, Suit::Heart).unwrap(),
                         Card::new(2, Suit::Spade).unwrap(),
                         Card::new(10, Suit::Diamond).unwrap(),
                         Card::new(9, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
             ),
             (
                 "Four of a Kind",
                 PokerHand {
                     poker_hand_type: None,
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(2, Suit::Club).unwrap(),
                         Card::new(10, Suit::Diamond).unwrap(),
                         Card::new(2, Suit::Spade).unwrap(),
                         Card::new(2, Suit::Heart).unwrap(),
                         Card::new(2, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::FourOfAKind),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(2, Suit::Club).unwrap(),
                         Card::new(2, Suit::Heart).unwrap(),
                         Card::new(2, Suit::Spade).unwrap(),
                         Card::new(2, Suit::Club).unwrap(),
                         Card::new(10, Suit::Diamond).unwrap(),
-                    ],
+                    ]),
                 },
             ),
             (
                 "Royal Flush",
                 PokerHand {
                     poker_hand_type: None,
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(12, Suit::Club).unwrap(),
                         Card::new(14, Suit::Club).unwrap(),
                         Card::new(10, Suit::Club).unwrap(),
                         Card::new(13, Suit::Club).unwrap(),
                         Card::new(11, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::RoyalFlush),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(14, Suit::Club).unwrap(),
                         Card::new(13, Suit::Club).unwrap(),
                         Card::new(12, Suit::Club).unwrap(),
                         Card::new(11, Suit::Club).unwrap(),
                         Card::new(10, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
             ),
             (
                 "Flush",
                 PokerHand {
                     poker_hand_type: None,
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(2, Suit::Club).unwrap(),
                         Card::new(14, Suit::Club).unwrap(),
                         Card::new(9, Suit::Club).unwrap(),
                         Card::new(13, Suit::Club).unwrap(),
                         Card::new(11, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::Flush),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(14, Suit::Club).unwrap(),
                         Card::new(13, Suit::Club).unwrap(),
                         Card::new(11, Suit::Club).unwrap(),
                         Card::new(9, Suit::Club).unwrap(),
                         Card::new(2, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
             ),
             (
                 "Straight Flush",
                 PokerHand {
                     poker_hand_type: None,
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(7, Suit::Club).unwrap(),
                         Card::new(10, Suit::Club).unwrap(),
                         Card::new(9, Suit::Club).unwrap(),
                         Card::new(8, Suit::Club).unwrap(),
                         Card::new(11, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::StraightFlush),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(11, Suit::Club).unwrap(),
                         Card::new(10, Suit::Club).unwrap(),
                         Card::new(9, Suit::Club).unwrap(),
                         Card::new(8, Suit::Club).unwrap(),
                         Card::new(7, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
             ),
             (
                 "Straight Flush -- Edge Case",
                 PokerHand {
                     poker_hand_type: None,
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(2, Suit::Club).unwrap(),
                         Card::new(14, Suit::Club).unwrap(),
                         Card::new(3, Suit::Club).unwrap(),
                         Card::new(5, Suit::Club).unwrap(),
                         Card::new(4, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::StraightFlush),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(5, Suit::Club).unwrap(),
                         Card::new(4, Suit::Club).unwrap(),
                         Card::new(3, Suit::Club).unwrap(),
                         Card::new(2, Suit::Club).unwrap(),
                         Card::new(14, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
             ),
             (
                 "Straight",
                 PokerHand {
                     poker_hand_type: None,
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(7, Suit::Club).unwrap(),
                         Card::new(10, Suit::Heart).unwrap(),
                         Card::new(9, Suit::Club).unwrap(),
                         Card::new(8, Suit::Club).unwrap(),
                         Card::new(11, Suit::Spade).unwrap(),
-                    ],
+                    ]),
                 },
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::Straight),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(11, Suit::Spade).unwrap(),
                         Card::new(10, Suit::Heart).unwrap(),
                         Card::new(9, Suit::Club).unwrap(),
                         Card::new(8, Suit::Club).unwrap(),
                         Card::new(7, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
             ),
             (
                 "Straight -- Edge Case",
                 PokerHand {
                     poker_hand_type: None,
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(2, Suit::Heart).unwrap(),
                         Card::new(14, Suit::Club).unwrap(),
                         Card::new(3, Suit::Diamond).unwrap(),
                         Card::new(5, Suit::Club).unwrap(),
                         Card::new(4, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::Straight),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(5, Suit::Club).unwrap(),
                         Card::new(4, Suit::Club).unwrap(),
                         Card::new(3, Suit::Diamond).unwrap(),
                         Card::new(2, Suit::Heart).unwrap(),
                         Card::new(14, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
             ),
             (
                 "High Card",
                 PokerHand {
                     poker_hand_type: None,
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(2, Suit::Heart).unwrap(),
                         Card::new(14, Suit::Club).unwrap(),
                         Card::new(8, Suit::Diamond).unwrap(),
                         Card::new(5, Suit::Club).unwrap(),
                         Card::new(11, Suit::Club).unwrap(),
-                    ],
+                    ]),
                 },
                 PokerHand {
                     poker_hand_type: Some(PokerHandType::HighCard),
-                    cards: vec![
+                    cards: HandBuilder::from([
                         Card::new(14, Suit::Club).unwrap(),
                         Card::new(11, Suit::Club).unwrap(),
                         Card::new(8, Suit::Diamond).unwrap(),
                         Card::new(5, Suit::Club).unwrap(),
                         Card::new(2, Suit::Heart).unwrap(),
-                    ],
+                    ]),
                 },
             ),
         ];
@@ -659,4 +870,156 @@ mod tests {
             assert_eq!(poker_hand, expected, "Case {name} failed");
         }
     }
+
+    #[test]
+    fn suit_order() {
+        let mut suits = vec![Suit::Heart, Suit::Club, Suit::Spade, Suit::Diamond];
+        suits.sort();
+        assert_eq!(
+            suits,
+            vec![Suit::Club, Suit::Diamond, Suit::Heart, Suit::Spade]
+        );
+
+        let big_two =
+            SuitOrder::custom([Suit::Diamond, Suit::Club, Suit::Heart, Suit::Spade]).unwrap();
+        assert_eq!(
+            big_two.compare(Suit::Club, Suit::Diamond),
+            Ordering::Greater
+        );
+        assert_eq!(
+            SuitOrder::BRIDGE.compare(Suit::Club, Suit::Diamond),
+            Ordering::Less
+        );
+
+        assert!(SuitOrder::custom([Suit::Club, Suit::Club, Suit::Heart, Suit::Spade]).is_err());
+    }
+
+    #[test]
+    fn poker_hands_cmp_with_tie_break() {
+        let mut spades = PokerHand::new();
+        let mut hearts = PokerHand::new();
+        for rank in [14, 13, 12, 11, 9] {
+            spades
+                .add_card(Card::new(rank, Suit::Spade).unwrap())
+                .unwrap();
+            hearts
+                .add_card(Card::new(rank, Suit::Heart).unwrap())
+                .unwrap();
+        }
+        spades.sort_hand().unwrap();
+        hearts.sort_hand().unwrap();
+
+        let cases = vec![
+            ("No tie break", None, Ordering::Equal),
+            (
+                "Alphabetical",
+                Some(SuitOrder::ALPHABETICAL),
+                Ordering::Greater,
+            ),
+            (
+                "Custom -- hearts on top",
+                Some(
+                    SuitOrder::custom([Suit::Club, Suit::Diamond, Suit::Spade, Suit::Heart])
+                        .unwrap(),
+                ),
+                Ordering::Less,
+            ),
+        ];
+
+        for (name, suit_order, expected) in cases {
+            assert_eq!(
+                spades.cmp_with_tie_break(&hearts, suit_order),
+                expected,
+                "Case: {name}"
+            );
+        }
+    }
+
+    #[test]
+    fn card_parse_and_display() {
+        let cases = vec![
+            ("Ah", Ok((14, Suit::Heart))),
+            ("Td", Ok((10, Suit::Diamond))),
+            ("10d", Ok((10, Suit::Diamond))),
+            ("2c", Ok((2, Suit::Club))),
+            ("ks", Ok((13, Suit::Spade))),
+            ("1s", Err(())),
+            ("Ax", Err(())),
+            ("", Err(())),
+        ];
+
+        for (text, expected) in cases {
+            let card = text.parse::<Card>();
+            assert_eq!(
+                card.as_ref()
+                    .map(|card| (card.rank, card.suit))
+                    .map_err(|_| ()),
+                expected,
+                "Case: {text}"
+            );
+        }
+
+        let cards = parse_cards("AhKh Qh,Jh  Th").unwrap();
+        let text: Vec<String> = cards.iter().map(|card| card.to_string()).collect();
+        assert_eq!(text, vec!["Ah", "Kh", "Qh", "Jh", "Th"]);
+        assert!(parse_cards("AhK").is_err());
+    }
+
+    #[test]
+    fn poker_hand_description() {
+        let cases = [
+            ("Ah Kh Qh Jh Th", "Royal flush"),
+            ("9s Ks Qs Js Ts", "Straight flush, King high"),
+            ("5d 4c 3h 2s Ad", "Straight, Five high"),
+            ("7c 7d 7h 7s 2c", "Four of a kind, Sevens"),
+            ("3c Kd 3h Ks Kc", "Full house, Kings full of Threes"),
+            ("2h 9h 4h Jh 6h", "Flush, Jack high"),
+            ("6c 6d 6h As 2c", "Three of a kind, Sixes"),
+            ("Jc 4d Jh 4s Ac", "Two pair, Jacks and Fours"),
+            ("9c 9d 2h 5s Ac", "Pair of Nines"),
+            ("Ac 9d 2h 5s Kc", "High card Ace"),
+        ];
+
+        for (cards, expected) in cases {
+            let hand = PokerHand::best_of(&parse_cards(cards).unwrap()).unwrap();
+            assert_eq!(hand.description().as_deref(), Some(expected), "{cards}");
+            assert_eq!(hand.cards().len(), 5);
+        }
+
+        assert_eq!(PokerHand::new().description(), None);
+        assert_eq!(PokerHandType::FullHouse.to_string(), "Full house");
+    }
+
+    #[test]
+    fn partial_hand_helpers() {
+        let cards = |text: &str| parse_cards(text).unwrap();
+
+        assert_eq!(most_suited(&[]), None);
+        assert!(PokerHand::new().set_hand_type().is_err());
+        assert_eq!(most_suited(&cards("Ah 7h 2c Kh")), Some((Suit::Heart, 3)));
+
+        assert_eq!(straight_high(rank_bits(&cards("Ah 2c 3d 4s 5h"))), Some(5));
+        assert_eq!(
+            straight_high(rank_bits(&cards("Ah Kc Qd Js Th 9c"))),
+            Some(14)
+        );
+        assert_eq!(straight_high(rank_bits(&cards("Ah Kc Qd Js 9h"))), None);
+
+        assert_eq!(
+            straight_draw_ranks(rank_bits(&cards("8c 9d Ts Jh"))),
+            vec![7, 12]
+        );
+        assert_eq!(
+            straight_draw_ranks(rank_bits(&cards("8c 9d Js Qh"))),
+            vec![10]
+        );
+        assert_eq!(
+            straight_draw_ranks(rank_bits(&cards("Ac 2d 3s 4h"))),
+            vec![5]
+        );
+        assert_eq!(
+            straight_draw_ranks(rank_bits(&cards("2c 7d Ks"))),
+            Vec::<u8>::new()
+        );
+    }
 }
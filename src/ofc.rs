@@ -0,0 +1,379 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::partial::PartialHand;
+use crate::{Card, PokerHand, PokerHandType, encoding};
+
+/// Bonus for winning all three rows.
+pub const SCOOP_BONUS: i32 = 3;
+
+/// Three card rows only count high card, pairs and trips.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Row {
+    hand_type: PokerHandType,
+    // Distinct ranks from most to least important, so that a three card row and a five card
+    // row compare the way they are read.
+    ranks: Vec<u8>,
+}
+
+impl Row {
+    fn top(cards: &[Card]) -> Result<Self, String> {
+        let partial = PartialHand::classify(cards)?;
+        Ok(Row {
+            hand_type: partial.made,
+            ranks: partial.ranks,
+        })
+    }
+
+    fn five(hand: &PokerHand) -> Self {
+        let mut ranks: Vec<u8> = hand.cards().iter().map(|card| card.rank).collect();
+        ranks.dedup();
+        Row {
+            hand_type: hand
+                .poker_hand_type()
+                .expect("best_of sorts the hand")
+                .clone(),
+            ranks,
+        }
+    }
+}
+
+/// One player's finished Open-Face Chinese poker hand: a three card top row and five card
+/// middle and bottom rows. Rows have to get strictly stronger from the top down, or the hand
+/// fouls.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OfcHand {
+    top: Vec<Card>,
+    middle: PokerHand,
+    bottom: PokerHand,
+}
+
+/// Royalties a hand earns, row by row.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Royalties {
+    pub top: i32,
+    pub middle: i32,
+    pub bottom: i32,
+}
+
+impl Royalties {
+    pub fn total(&self) -> i32 {
+        self.top + self.middle + self.bottom
+    }
+}
+
+/// How one hand did against another, from the first hand's side. The other hand scores the
+/// negative of every field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Matchup {
+    /// 1 for a row won, -1 for a row lost, 0 for a tie. Top, middle, bottom.
+    pub rows: [i32; 3],
+    /// `SCOOP_BONUS` for winning every row, minus that for losing every row.
+    pub scoop: i32,
+    /// Our royalties minus theirs.
+    pub royalties: i32,
+}
+
+impl Matchup {
+    pub fn total(&self) -> i32 {
+        self.rows.iter().sum::<i32>() + self.scoop + self.royalties
+    }
+}
+
+impl OfcHand {
+    /// Sets a hand from its three rows. Fouled hands are allowed; they just score badly.
+    pub fn new(top: &[Card], middle: &[Card], bottom: &[Card]) -> Result<Self, String> {
+        if top.len() != 3 {
+            return Err(format!("The top row takes 3 cards, not {}", top.len()));
+        }
+        for (name, row) in [("middle", middle), ("bottom", bottom)] {
+            if row.len() != 5 {
+                return Err(format!("The {name} row takes 5 cards, not {}", row.len()));
+            }
+        }
+        encoding::mask(&[top, middle, bottom].concat())?;
+
+        Ok(OfcHand {
+            top: top.to_vec(),
+            middle: PokerHand::best_of(middle)?,
+            bottom: PokerHand::best_of(bottom)?,
+        })
+    }
+
+    pub fn top(&self) -> &[Card] {
+        &self.top
+    }
+
+    pub fn middle(&self) -> &PokerHand {
+        &self.middle
+    }
+
+    pub fn bottom(&self) -> &PokerHand {
+        &self.bottom
+    }
+
+    fn rows(&self) -> [Row; 3] {
+        [
+            Row::top(&self.top).expect("new checked the top row"),
+            Row::five(&self.middle),
+            Row::five(&self.bottom),
+        ]
+    }
+
+    /// `true` when the middle row is not stronger than the top row or the bottom row is not
+    /// stronger than the middle row.
+    pub fn is_foul(&self) -> bool {
+        let [top, middle, bottom] = self.rows();
+        top >= middle || middle >= bottom
+    }
+
+    /// Royalties for each row. A fouled hand earns none.
+    pub fn royalties(&self) -> Royalties {
+        if self.is_foul() {
+            return Royalties::default();
+        }
+
+        let [top, middle, bottom] = self.rows();
+        Royalties {
+            top: top_royalty(&top),
+            middle: middle_royalty(&middle.hand_type),
+            bottom: bottom_royalty(&bottom.hand_type),
+        }
+    }
+
+    /// Queens or better on top without fouling earns a Fantasyland hand.
+    pub fn fantasyland(&self) -> bool {
+        let top = Row::top(&self.top).expect("new checked the top row");
+        !self.is_foul()
+            && (top.hand_type == PokerHandType::ThreeOfAKind
+                || top.hand_type == PokerHandType::OnePair && top.ranks[0] >= 12)
+    }
+
+    /// Scores this hand against `other`: a point for each row won, `SCOOP_BONUS` more for
+    /// winning all three, and the difference in royalties on top.
+    ///
+    /// A fouled hand loses every row and gets scooped, and the other hand keeps its royalties.
+    /// Two fouled hands score nothing.
+    pub fn against(&self, other: &OfcHand) -> Matchup {
+        let rows = match (self.is_foul(), other.is_foul()) {
+            (true, true) => return Matchup::default(),
+            (true, false) => [-1; 3],
+            (false, true) => [1; 3],
+            (false, false) => {
+                let mut rows = [0; 3];
+                for (row, (ours, theirs)) in
+                    rows.iter_mut().zip(self.rows().iter().zip(&other.rows()))
+                {
+                    *row = ours.cmp(theirs) as i32;
+                }
+                rows
+            }
+        };
+
+        let scoop = match rows {
+            [1, 1, 1] => SCOOP_BONUS,
+            [-1, -1, -1] => -SCOOP_BONUS,
+            _ => 0,
+        };
+        Matchup {
+            rows,
+            scoop,
+            royalties: self.royalties().total() - other.royalties().total(),
+        }
+    }
+}
+
+/// Scores every hand against every other hand and adds up the points for each player. The
+/// totals add up to zero.
+pub fn score(hands: &[OfcHand]) -> Vec<i32> {
+    let mut totals = vec![0; hands.len()];
+    for i in 0..hands.len() {
+        for j in i + 1..hands.len() {
+            let points = hands[i].against(&hands[j]).total();
+            totals[i] += points;
+            totals[j] -= points;
+        }
+    }
+    totals
+}
+
+// 66 is worth 1 up to 9 for AA, then 10 for 222 up to 22 for AAA.
+fn top_royalty(top: &Row) -> i32 {
+    match top.hand_type {
+        PokerHandType::ThreeOfAKind => top.ranks[0] as i32 + 8,
+        PokerHandType::OnePair if top.ranks[0] >= 6 => top.ranks[0] as i32 - 5,
+        _ => 0,
+    }
+}
+
+fn middle_royalty(hand_type: &PokerHandType) -> i32 {
+    match hand_type {
+        PokerHandType::ThreeOfAKind => 2,
+        hand_type => 2 * bottom_royalty(hand_type),
+    }
+}
+
+fn bottom_royalty(hand_type: &PokerHandType) -> i32 {
+    match hand_type {
+        PokerHandType::Straight => 2,
+        PokerHandType::Flush => 4,
+        PokerHandType::FullHouse => 6,
+        PokerHandType::FourOfAKind => 10,
+        PokerHandType::StraightFlush => 15,
+        PokerHandType::RoyalFlush => 25,
+        _ => 0,
+    }
+}
+
+impl fmt::Display for OfcHand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let row = |cards: &[Card]| {
+            cards
+                .iter()
+                .map(Card::to_string)
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        write!(
+            f,
+            "{} / {} / {}",
+            row(&self.top),
+            row(self.middle.cards()),
+            row(self.bottom.cards())
+        )
+    }
+}
+
+/// Parses the rows top to bottom, separated by `/`, e.g. `Qh Qd 5c / 9s 9d 4c 4h 2s / ...`.
+impl FromStr for OfcHand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows: Vec<Vec<Card>> = s
+            .split('/')
+            .map(crate::parse_cards)
+            .collect::<Result<_, _>>()?;
+        match rows.as_slice() {
+            [top, middle, bottom] => OfcHand::new(top, middle, bottom),
+            _ => Err(format!(
+                "A hand has 3 rows separated by '/', not {}",
+                rows.len()
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(text: &str) -> OfcHand {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn ofc_fouls_and_royalties() {
+        let good = hand("Qh Qd 5c / Ks Kd 4c 4h 2s / 7c 8c 9c Tc 3c");
+        assert!(!good.is_foul());
+        assert_eq!(
+            good.royalties(),
+            Royalties {
+                top: 7,
+                middle: 0,
+                bottom: 4
+            }
+        );
+        assert!(good.fantasyland());
+
+        // Queens on top need more than queens in the middle.
+        assert!(!hand("Qh Qd 5c / Qs Qc 4c 4h 2s / 7c 8c 9c Tc 3c").is_foul());
+        assert!(!hand("Qh Qd 5c / Qs Qc 5h 3h 2s / 7c 8c 9c Tc 3c").is_foul());
+        assert!(hand("Qh Qd 5c / Qs Qc 4c 3h 2s / 7c 8c 9c Tc 3c").is_foul());
+        let foul = hand("Qh Qd Kc / Qs Qc 4c 3h 2s / 7c 8c 9c Tc 3c");
+        assert!(foul.is_foul());
+        assert_eq!(foul.royalties().total(), 0);
+        assert!(!foul.fantasyland());
+
+        // The bottom has to beat the middle too.
+        assert!(hand("2h 3d 4c / 9s 9c Ac Kh Qs / 8c 8d Ad Kc Qd").is_foul());
+
+        // Trips on top, a flush in the middle and quads on the bottom.
+        let monster = hand("5h 5d 5c / 2s 6s 9s Js Ks / Ac Ad Ah As 3d");
+        assert_eq!(
+            monster.royalties(),
+            Royalties {
+                top: 13,
+                middle: 8,
+                bottom: 10
+            }
+        );
+        assert_eq!(
+            hand("Ah Kd 2c / 3s 3c 3d 8h 9s / Tc Jc Qc Kc Ac").royalties(),
+            Royalties {
+                top: 0,
+                middle: 2,
+                bottom: 25
+            }
+        );
+        assert_eq!(
+            hand("6h 6d 2c / 7s 7c 3d 3h 9s / Tc Jd Qc Kh Ac")
+                .royalties()
+                .top,
+            1
+        );
+    }
+
+    #[test]
+    fn ofc_scoring() {
+        let a = hand("Qh Qd 5c / Ks Kd 4c 4h 2s / 7c 8c 9c Tc 3c");
+        let b = hand("Ah Kd 2c / 9s 9c 3d 3h 8s / Jc Jd Js 5h 5d");
+        // Queens beat ace high on top, b's full house beats a's flush, a's kings up win the
+        // middle. Royalties: a has 7 + 4, b has 6.
+        let matchup = a.against(&b);
+        assert_eq!(matchup.rows, [1, 1, -1]);
+        assert_eq!(matchup.scoop, 0);
+        assert_eq!(matchup.royalties, 5);
+        assert_eq!(matchup.total(), 6);
+        assert_eq!(b.against(&a).total(), -6);
+
+        // A fouled hand is scooped and pays the royalties of the other hand.
+        let foul = hand("Kh Kc 2d / Qs Qc 4d 3h 2s / 7d 8d 9d Td 3d");
+        let matchup = a.against(&foul);
+        assert_eq!(
+            (matchup.rows, matchup.scoop, matchup.royalties),
+            ([1, 1, 1], 3, 11)
+        );
+        assert_eq!(foul.against(&foul).total(), 0);
+
+        assert_eq!(
+            score(&[a.clone(), b.clone(), foul.clone()]),
+            vec![23, 6, -29]
+        );
+        assert_eq!(score(&[a, b, foul]).iter().sum::<i32>(), 0);
+    }
+
+    #[test]
+    fn ofc_parse_errors() {
+        assert!(
+            "Qh Qd / Ks Kd 4c 4h 2s / 7c 8c 9c Tc 3c"
+                .parse::<OfcHand>()
+                .is_err()
+        );
+        assert!(
+            "Qh Qd 5c / Ks Kd 4c 4h / 7c 8c 9c Tc 3c"
+                .parse::<OfcHand>()
+                .is_err()
+        );
+        assert!("Qh Qd 5c / Ks Kd 4c 4h 2s".parse::<OfcHand>().is_err());
+        assert_eq!(
+            "Qh Qd 5c / Ks Kd 4c 4h 2s / 7c 8c 9c Tc 5c"
+                .parse::<OfcHand>()
+                .unwrap_err(),
+            "5c shows up twice"
+        );
+        assert_eq!(
+            hand("Qh Qd 5c / Ks Kd 4c 4h 2s / 7c 8c 9c Tc 3c").to_string(),
+            "Qh Qd 5c / Ks Kd 4c 4h 2s / Tc 9c 8c 7c 3c"
+        );
+    }
+}
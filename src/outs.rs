@@ -0,0 +1,346 @@
+use std::fmt;
+
+use crate::deck::Deck;
+use crate::{
+    Card, PokerHand, PokerHandType, combinations, encoding, most_suited, rank_bits,
+    straight_draw_ranks,
+};
+
+/// The kind of draw a group of outs completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Draw {
+    /// One more card of the suit makes a flush.
+    FlushDraw,
+    /// Two ranks make a straight: open-ended draws and double gutshots.
+    OpenEnded,
+    /// Only one rank makes a straight.
+    Gutshot,
+    /// Pairs, trips, two pair, full houses and quads.
+    Pairing,
+    /// Two more cards of the suit make a flush. Flop only.
+    BackdoorFlush,
+    /// Two more cards make a straight. Flop only.
+    BackdoorStraight,
+}
+
+impl Draw {
+    /// `true` for draws that need both the turn and the river.
+    pub fn is_backdoor(&self) -> bool {
+        matches!(self, Draw::BackdoorFlush | Draw::BackdoorStraight)
+    }
+}
+
+impl fmt::Display for Draw {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Draw::FlushDraw => "Flush draw",
+            Draw::OpenEnded => "Open-ended straight draw",
+            Draw::Gutshot => "Gutshot",
+            Draw::Pairing => "Pairing",
+            Draw::BackdoorFlush => "Backdoor flush",
+            Draw::BackdoorStraight => "Backdoor straight",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Unseen cards that complete one kind of draw.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutsGroup {
+    pub draw: Draw,
+    /// The outs, in deck order. For backdoor draws these are the cards that can start the
+    /// draw on the turn.
+    pub cards: Vec<Card>,
+    /// Chance of hitting the draw by the river.
+    pub probability: f64,
+}
+
+/// Every unseen card that improves a Hold'em hand on the flop or the turn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outs {
+    /// What the hand makes now.
+    pub current: PokerHandType,
+    /// Outs grouped by draw, strongest draws first.
+    pub groups: Vec<OutsGroup>,
+    /// Chance of improving by the river, counting every runout including backdoors.
+    pub probability: f64,
+}
+
+impl Outs {
+    /// Number of single-card outs, leaving out backdoor draws.
+    pub fn count(&self) -> usize {
+        self.groups
+            .iter()
+            .filter(|group| !group.draw.is_backdoor())
+            .map(|group| group.cards.len())
+            .sum()
+    }
+}
+
+/// Finds the unseen cards that improve `hole` (two cards) on `board` (a flop or a turn) to a
+/// better `PokerHandType`.
+///
+/// With an `opponent` hand their cards are no longer unseen, and only cards that leave `hole`
+/// beating the opponent outright count as outs.
+pub fn outs(hole: &[Card], board: &[Card], opponent: Option<&[Card]>) -> Result<Outs, String> {
+    if hole.len() != 2 {
+        return Err(format!("Hands must have 2 cards, not {}", hole.len()));
+    }
+    if !(3..=4).contains(&board.len()) {
+        return Err(format!(
+            "Outs need a flop or a turn, not {} board cards",
+            board.len()
+        ));
+    }
+    if let Some(opponent) = opponent
+        && opponent.len() != 2
+    {
+        return Err(format!("Hands must have 2 cards, not {}", opponent.len()));
+    }
+
+    let mut known = hole.to_vec();
+    known.extend(board);
+    known.extend(opponent.unwrap_or_default());
+    encoding::mask(&known)?;
+
+    let mut deck = Deck::new();
+    deck.remove(&known);
+    let unseen = deck.cards();
+
+    let mut cards = hole.to_vec();
+    cards.extend(board);
+    let current = hand_type(&cards)?;
+
+    // Whether the hand improves, and wins if there is an opponent, once `extra` comes.
+    let hits = |extra: &[Card]| -> Result<Option<PokerHandType>, String> {
+        let mut with = cards.clone();
+        with.extend(extra);
+        let hand = PokerHand::best_of(&with)?;
+        let hand_type = hand
+            .poker_hand_type()
+            .expect("best_of sorts the hand")
+            .clone();
+        if hand_type <= current {
+            return Ok(None);
+        }
+        if let Some(opponent) = opponent {
+            let mut theirs = opponent.to_vec();
+            theirs.extend(board);
+            theirs.extend(extra);
+            if hand <= PokerHand::best_of(&theirs)? {
+                return Ok(None);
+            }
+        }
+        Ok(Some(hand_type))
+    };
+
+    let mut groups: Vec<OutsGroup> = Vec::new();
+    let mut is_out = vec![false; unseen.len()];
+    for (i, &card) in unseen.iter().enumerate() {
+        if let Some(hand_type) = hits(&[card])? {
+            is_out[i] = true;
+            add_card(&mut groups, classify(&cards, &current, &hand_type), card);
+        }
+    }
+
+    let left = unseen.len() as u64;
+    let probability;
+    if board.len() == 4 {
+        for group in &mut groups {
+            group.probability = group.cards.len() as f64 / left as f64;
+        }
+        probability = is_out.iter().filter(|&&out| out).count() as f64 / left as f64;
+    } else {
+        let runouts = encoding::binomial(left as u32, 2);
+        for group in &mut groups {
+            let misses = encoding::binomial((left as usize - group.cards.len()) as u32, 2);
+            group.probability = 1.0 - misses as f64 / runouts as f64;
+        }
+
+        // Deal every turn and river. Pairs where neither card is an out on its own are the
+        // backdoor draws.
+        let mut improved = 0;
+        let mut backdoors: Vec<(Draw, u64, Vec<Card>)> = Vec::new();
+        for pair in combinations(unseen.len(), 2) {
+            let (a, b) = (pair[0], pair[1]);
+            if is_out[a] || is_out[b] {
+                improved += 1;
+                continue;
+            }
+            let Some(hand_type) = hits(&[unseen[a], unseen[b]])? else {
+                continue;
+            };
+            improved += 1;
+            let draw = match hand_type {
+                PokerHandType::Flush | PokerHandType::StraightFlush | PokerHandType::RoyalFlush => {
+                    Draw::BackdoorFlush
+                }
+                PokerHandType::Straight => Draw::BackdoorStraight,
+                _ => continue,
+            };
+            let at = match backdoors.iter().position(|(found, _, _)| *found == draw) {
+                Some(at) => at,
+                None => {
+                    backdoors.push((draw, 0, Vec::new()));
+                    backdoors.len() - 1
+                }
+            };
+            let (_, count, starts) = &mut backdoors[at];
+            *count += 1;
+            for card in [unseen[a], unseen[b]] {
+                if !starts.iter().any(|start| start.is_same_card(&card)) {
+                    starts.push(card);
+                }
+            }
+        }
+
+        for (draw, count, mut starts) in backdoors {
+            starts.sort_by_key(|&card| encoding::card_to_bits(card));
+            groups.push(OutsGroup {
+                draw,
+                cards: starts,
+                probability: count as f64 / runouts as f64,
+            });
+        }
+        probability = improved as f64 / runouts as f64;
+    }
+
+    groups.sort_by_key(|group| group.draw);
+    Ok(Outs {
+        current,
+        groups,
+        probability,
+    })
+}
+
+fn hand_type(cards: &[Card]) -> Result<PokerHandType, String> {
+    let hand = PokerHand::best_of(cards)?;
+    Ok(hand
+        .poker_hand_type()
+        .expect("best_of sorts the hand")
+        .clone())
+}
+
+// Which draw a card that takes `cards` from `current` up to `made` completes.
+fn classify(cards: &[Card], current: &PokerHandType, made: &PokerHandType) -> Draw {
+    let straight = || {
+        if straight_draw_ranks(rank_bits(cards)).len() >= 2 {
+            Draw::OpenEnded
+        } else {
+            Draw::Gutshot
+        }
+    };
+    let four_suited = most_suited(cards).is_some_and(|(_, count)| count >= 4);
+
+    match made {
+        PokerHandType::Flush => Draw::FlushDraw,
+        PokerHandType::Straight => straight(),
+        PokerHandType::StraightFlush | PokerHandType::RoyalFlush
+            if four_suited && *current < PokerHandType::Flush =>
+        {
+            Draw::FlushDraw
+        }
+        PokerHandType::StraightFlush | PokerHandType::RoyalFlush => straight(),
+        _ => Draw::Pairing,
+    }
+}
+
+fn add_card(groups: &mut Vec<OutsGroup>, draw: Draw, card: Card) {
+    match groups.iter_mut().find(|group| group.draw == draw) {
+        Some(group) => group.cards.push(card),
+        None => groups.push(OutsGroup {
+            draw,
+            cards: vec![card],
+            probability: 0.0,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_cards;
+
+    fn cards(text: &str) -> Vec<Card> {
+        parse_cards(text).unwrap()
+    }
+
+    fn group(outs: &Outs, draw: Draw) -> &OutsGroup {
+        outs.groups
+            .iter()
+            .find(|group| group.draw == draw)
+            .unwrap_or_else(|| panic!("No {draw} in {outs:?}"))
+    }
+
+    fn text(cards: &[Card]) -> String {
+        cards
+            .iter()
+            .map(|card| card.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn outs_on_the_flop() {
+        // Nut flush draw with two overcards and a backdoor straight.
+        let outs = outs(&cards("Ah Kh"), &cards("Qh 7c 2h"), None).unwrap();
+        assert_eq!(outs.current, PokerHandType::HighCard);
+
+        let flush = group(&outs, Draw::FlushDraw);
+        assert_eq!(text(&flush.cards), "3h 4h 5h 6h 7h 8h 9h Th Jh");
+        // 1 - C(38, 2) / C(47, 2)
+        assert!((flush.probability - (1.0 - 703.0 / 1081.0)).abs() < 1e-12);
+
+        // Every non-heart ace, king, queen, seven and deuce pairs something.
+        assert_eq!(group(&outs, Draw::Pairing).cards.len(), 14);
+        assert_eq!(outs.count(), 23);
+        assert!(group(&outs, Draw::BackdoorStraight).probability > 0.0);
+        assert!(outs.groups.iter().all(|group| group.draw != Draw::Gutshot));
+        assert!(outs.probability > flush.probability);
+        assert!(outs.probability < 1.0);
+
+        // Open-ended against a gutshot.
+        let open = super::outs(&cards("9c 8d"), &cards("Ts Jh 2c"), None).unwrap();
+        assert_eq!(
+            text(&group(&open, Draw::OpenEnded).cards),
+            "7c Qc 7d Qd 7h Qh 7s Qs"
+        );
+        let gutshot = super::outs(&cards("9c 8d"), &cards("Qs Jh 2c"), None).unwrap();
+        assert_eq!(text(&group(&gutshot, Draw::Gutshot).cards), "Tc Td Th Ts");
+    }
+
+    #[test]
+    fn outs_on_the_turn() {
+        let outs = outs(&cards("9c 8d"), &cards("Ts Jh 2c 4d"), None).unwrap();
+        let open = group(&outs, Draw::OpenEnded);
+        assert_eq!(open.cards.len(), 8);
+        assert!((open.probability - 8.0 / 46.0).abs() < 1e-12);
+        assert!(outs.groups.iter().all(|group| !group.draw.is_backdoor()));
+        // Straights plus three cards of each of the six ranks already out.
+        assert_eq!(group(&outs, Draw::Pairing).cards.len(), 18);
+        assert_eq!(outs.count(), 26);
+        assert!((outs.probability - 26.0 / 46.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn outs_against_an_opponent() {
+        // Against a set of sevens: pairing the ace or king no longer helps, and the seven of
+        // hearts gives the opponent quads.
+        let villain = cards("7d 7s");
+        let outs = outs(&cards("Ah Kh"), &cards("Qh 7c 2h"), Some(&villain)).unwrap();
+        assert_eq!(
+            text(&group(&outs, Draw::FlushDraw).cards),
+            "3h 4h 5h 6h 8h 9h Th Jh"
+        );
+        assert_eq!(outs.count(), 8);
+        assert!(outs.groups.iter().all(|group| group.draw != Draw::Pairing));
+
+        assert_eq!(
+            super::outs(&cards("Ah Kh"), &cards("Qh 7c 2h"), Some(&cards("Ah 3c"))).unwrap_err(),
+            "Ah shows up twice"
+        );
+        assert!(super::outs(&cards("Ah Kh"), &cards("Qh 7c"), None).is_err());
+        assert!(super::outs(&cards("Ah"), &cards("Qh 7c 2h"), None).is_err());
+        assert!(super::outs(&cards("Ah Kh"), &cards("Qh 7c 2h"), Some(&villain[..1])).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::{
+    Card, PokerHandType, encoding, most_suited, rank_bits, rank_name, rank_plural,
+    straight_draw_ranks,
+};
+
+/// What a hand of fewer than five cards makes so far, and what it draws to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialHand {
+    /// One of `HighCard`, `OnePair`, `TwoPair`, `ThreeOfAKind` or `FourOfAKind`. `HighCard` for
+    /// an empty hand too.
+    pub made: PokerHandType,
+    /// Ranks from most to least important: the pairs or trips first, then the kickers.
+    pub ranks: Vec<u8>,
+    /// Four cards of one suit.
+    pub four_flush: bool,
+    /// Four ranks in a row that either end makes a straight of.
+    pub open_ended: bool,
+}
+
+impl PartialHand {
+    /// Classifies zero to four `cards`.
+    pub fn classify(cards: &[Card]) -> Result<Self, String> {
+        if cards.len() > 4 {
+            return Err(format!(
+                "Partial hands have at most 4 cards, not {}",
+                cards.len()
+            ));
+        }
+        encoding::mask(cards)?;
+
+        let mut counts: Vec<(usize, u8)> = Vec::new();
+        for card in cards {
+            match counts.iter_mut().find(|(_, rank)| *rank == card.rank) {
+                Some((count, _)) => *count += 1,
+                None => counts.push((1, card.rank)),
+            }
+        }
+        counts.sort_by(|a, b| b.cmp(a));
+
+        let made = match counts.as_slice() {
+            [(4, _), ..] => PokerHandType::FourOfAKind,
+            [(3, _), ..] => PokerHandType::ThreeOfAKind,
+            [(2, _), (2, _), ..] => PokerHandType::TwoPair,
+            [(2, _), ..] => PokerHandType::OnePair,
+            _ => PokerHandType::HighCard,
+        };
+
+        Ok(PartialHand {
+            made,
+            ranks: counts.iter().map(|&(_, rank)| rank).collect(),
+            four_flush: most_suited(cards).is_some_and(|(_, count)| count == 4),
+            open_ended: cards.len() == 4
+                && counts.len() == 4
+                && straight_draw_ranks(rank_bits(cards)).len() == 2,
+        })
+    }
+}
+
+/// Made hand first, then the ranks. Draws do not count.
+impl Ord for PartialHand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.made
+            .cmp(&other.made)
+            .then_with(|| self.ranks.cmp(&other.ranks))
+    }
+}
+
+impl PartialOrd for PartialHand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for PartialHand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.made, self.ranks.as_slice()) {
+            (_, []) => write!(f, "No cards")?,
+            (PokerHandType::FourOfAKind, [rank, ..]) => {
+                write!(f, "Four of a kind, {}", rank_plural(*rank))?
+            }
+            (PokerHandType::ThreeOfAKind, [rank, ..]) => {
+                write!(f, "Three of a kind, {}", rank_plural(*rank))?
+            }
+            (PokerHandType::TwoPair, [high, low, ..]) => write!(
+                f,
+                "Two pair, {} and {}",
+                rank_plural(*high),
+                rank_plural(*low)
+            )?,
+            (PokerHandType::OnePair, [rank, ..]) => write!(f, "Pair of {}", rank_plural(*rank))?,
+            (_, [rank, ..]) => write!(f, "High card {}", rank_name(*rank))?,
+        }
+
+        if self.four_flush {
+            write!(f, ", four to a flush")?;
+        }
+        if self.open_ended {
+            write!(f, ", open-ended straight draw")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_cards;
+
+    fn classify(text: &str) -> PartialHand {
+        PartialHand::classify(&parse_cards(text).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn partial_hand_made_hands() {
+        let empty = PartialHand::classify(&[]).unwrap();
+        assert_eq!(empty.made, PokerHandType::HighCard);
+        assert_eq!(empty.to_string(), "No cards");
+
+        assert_eq!(classify("Kd").to_string(), "High card King");
+        assert_eq!(classify("9s 9h").to_string(), "Pair of Nines");
+        assert_eq!(classify("3c Kd 3s").ranks, vec![3, 13]);
+        assert_eq!(classify("6c 6d 6s").to_string(), "Three of a kind, Sixes");
+        assert_eq!(
+            classify("3c Kd 3s Kh").to_string(),
+            "Two pair, Kings and Threes"
+        );
+        assert_eq!(classify("2c 2d 2h 2s").to_string(), "Four of a kind, Twos");
+
+        // A three card front hand in Chinese poker: trips beat any pair, pairs go by rank
+        // and then kicker.
+        assert!(classify("2c 2d 2h") > classify("Ac Ad Kh"));
+        assert!(classify("Qc Qd 5h") > classify("Qh Qs 4c"));
+        assert!(classify("Ac Kd 2h") > classify("Kc Qd Jh"));
+        assert_eq!(
+            classify("Qc Qd 5h").cmp(&classify("Qh Qs 5c")),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn partial_hand_draws() {
+        assert_eq!(
+            classify("Ah 7h 2h Kh").to_string(),
+            "High card Ace, four to a flush"
+        );
+        assert_eq!(
+            classify("8c 9d Ts Jh").to_string(),
+            "High card Jack, open-ended straight draw"
+        );
+        let both = classify("8h 9h Th Jh");
+        assert!(both.four_flush && both.open_ended);
+
+        // One end only, a gutshot, three to a flush and a pair are not draws here.
+        assert!(!classify("Ac Kd Qs Jh").open_ended);
+        assert!(!classify("Ac 2d 3s 4h").open_ended);
+        assert!(!classify("8c 9d Js Qh").open_ended);
+        assert!(!classify("8h 9h Th").four_flush);
+        assert!(!classify("8c 8d 9s Th").open_ended);
+
+        assert!(PartialHand::classify(&parse_cards("2c 3c 4c 5c 6c").unwrap()).is_err());
+        assert_eq!(
+            PartialHand::classify(&parse_cards("2c 2c").unwrap()).unwrap_err(),
+            "2c shows up twice"
+        );
+    }
+}
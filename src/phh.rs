@@ -0,0 +1,894 @@
+use std::fmt;
+use std::fmt::Write as _;
+
+use crate::history::HandHistory;
+use crate::holdem::{Action, Street};
+use crate::{Card, PokerHand, parse_cards};
+
+/// A hand in the open Poker Hand History format (<https://phh.readthedocs.io>).
+///
+/// Players are numbered from 1 in the order PHH uses: starting left of the button, so the
+/// button is the last player. Amounts have to be whole numbers of chips.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PhhHand {
+    /// Game code, e.g. `NT` for no-limit Texas hold'em or `FT` for fixed-limit.
+    pub variant: String,
+    pub antes: Vec<u64>,
+    pub blinds_or_straddles: Vec<u64>,
+    pub min_bet: u64,
+    pub starting_stacks: Vec<u64>,
+    pub actions: Vec<PhhAction>,
+    pub players: Option<Vec<String>>,
+    /// Every other field, kept so they are written back out.
+    pub extra: Vec<(String, PhhValue)>,
+}
+
+/// One action line. Player numbers are 1 based like in the file, and `None` cards are the
+/// `??` placeholders for cards nobody saw.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PhhAction {
+    DealHole {
+        player: usize,
+        cards: Vec<Option<Card>>,
+    },
+    DealBoard(Vec<Card>),
+    Fold(usize),
+    CheckOrCall(usize),
+    CompleteBetOrRaiseTo {
+        player: usize,
+        amount: u64,
+    },
+    /// `None` when the player mucked.
+    ShowOrMuck {
+        player: usize,
+        cards: Option<Vec<Option<Card>>>,
+    },
+}
+
+/// The TOML values PHH files use.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PhhValue {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Array(Vec<PhhValue>),
+}
+
+/// Written as TOML. NaN and infinities come out as TOML's `nan` and `inf`, though `to_phh`
+/// refuses them.
+impl fmt::Display for PhhValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PhhValue::String(s) => write_string(f, s),
+            PhhValue::Integer(i) => write!(f, "{i}"),
+            PhhValue::Float(x) if x.is_nan() => write!(f, "nan"),
+            PhhValue::Float(x) if x.is_infinite() => {
+                write!(f, "{}inf", if *x < 0.0 { "-" } else { "" })
+            }
+            PhhValue::Float(x) => write!(f, "{x:?}"),
+            PhhValue::Boolean(b) => write!(f, "{b}"),
+            PhhValue::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+// A TOML basic string: quotes, backslashes and control characters escaped.
+fn write_string(out: &mut impl fmt::Write, s: &str) -> fmt::Result {
+    out.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\t' => out.write_str("\\t")?,
+            '\r' => out.write_str("\\r")?,
+            c if c.is_ascii_control() => write!(out, "\\u{:04X}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+// A bare TOML key if `key` can be one, a quoted one otherwise.
+fn write_key(out: &mut impl fmt::Write, key: &str) -> fmt::Result {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        out.write_str(key)
+    } else {
+        write_string(out, key)
+    }
+}
+
+// Whether `value` has a float TOML can hold but PHH has no use for.
+fn has_non_finite(value: &PhhValue) -> bool {
+    match value {
+        PhhValue::Float(x) => !x.is_finite(),
+        PhhValue::Array(values) => values.iter().any(has_non_finite),
+        _ => false,
+    }
+}
+
+impl fmt::Display for PhhAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PhhAction::DealHole { player, cards } => {
+                write!(f, "d dh p{player} {}", unknown_cards(cards))
+            }
+            PhhAction::DealBoard(cards) => {
+                write!(f, "d db ")?;
+                cards.iter().try_for_each(|card| write!(f, "{card}"))
+            }
+            PhhAction::Fold(player) => write!(f, "p{player} f"),
+            PhhAction::CheckOrCall(player) => write!(f, "p{player} cc"),
+            PhhAction::CompleteBetOrRaiseTo { player, amount } => {
+                write!(f, "p{player} cbr {amount}")
+            }
+            PhhAction::ShowOrMuck {
+                player,
+                cards: Some(cards),
+            } => write!(f, "p{player} sm {}", unknown_cards(cards)),
+            PhhAction::ShowOrMuck {
+                player,
+                cards: None,
+            } => write!(f, "p{player} sm"),
+        }
+    }
+}
+
+fn unknown_cards(cards: &[Option<Card>]) -> String {
+    cards
+        .iter()
+        .map(|card| card.map_or("??".to_string(), |card| card.to_string()))
+        .collect()
+}
+
+impl PhhAction {
+    /// Parses an action line like `p1 cbr 6` or `d db 2c7h9d`. Comments after `#` are dropped.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.split('#').next().unwrap_or_default();
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        let player = |word: &str| -> Result<usize, String> {
+            word.strip_prefix('p')
+                .and_then(|number| number.parse().ok())
+                .filter(|number| *number > 0)
+                .ok_or_else(|| format!("{word:?} is not a player in {line:?}"))
+        };
+
+        match words.as_slice() {
+            ["d", "dh", who, cards] => Ok(PhhAction::DealHole {
+                player: player(who)?,
+                cards: parse_unknown_cards(cards)?,
+            }),
+            ["d", "db", cards] => Ok(PhhAction::DealBoard(parse_cards(cards)?)),
+            [who, "f"] => Ok(PhhAction::Fold(player(who)?)),
+            [who, "cc"] => Ok(PhhAction::CheckOrCall(player(who)?)),
+            [who, "cbr", amount] => Ok(PhhAction::CompleteBetOrRaiseTo {
+                player: player(who)?,
+                amount: amount
+                    .parse()
+                    .map_err(|_| format!("{amount:?} is not a whole amount in {line:?}"))?,
+            }),
+            [who, "sm"] => Ok(PhhAction::ShowOrMuck {
+                player: player(who)?,
+                cards: None,
+            }),
+            [who, "sm", cards] => Ok(PhhAction::ShowOrMuck {
+                player: player(who)?,
+                cards: Some(parse_unknown_cards(cards)?),
+            }),
+            _ => Err(format!("{line:?} is not a supported action")),
+        }
+    }
+}
+
+fn parse_unknown_cards(text: &str) -> Result<Vec<Option<Card>>, String> {
+    if !text.len().is_multiple_of(2) {
+        return Err(format!("{text:?} is not a list of cards"));
+    }
+
+    (0..text.len())
+        .step_by(2)
+        .map(|i| match text.get(i..i + 2) {
+            Some("??") => Ok(None),
+            Some(card) => card.parse().map(Some),
+            None => Err(format!("{text:?} is not a list of cards")),
+        })
+        .collect()
+}
+
+impl PhhHand {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut hand = PhhHand::default();
+        let mut required = [
+            "variant",
+            "antes",
+            "blinds_or_straddles",
+            "min_bet",
+            "starting_stacks",
+            "actions",
+        ]
+        .map(|key| (key, false));
+
+        for (key, value) in parse_toml(text)? {
+            if let Some((_, seen)) = required.iter_mut().find(|(name, _)| *name == key) {
+                *seen = true;
+            }
+            let error = |expected: &str| format!("{key} must be {expected}");
+
+            match key.as_str() {
+                "variant" => hand.variant = string(&value).ok_or_else(|| error("a string"))?,
+                "antes" => {
+                    hand.antes = amounts(&value).ok_or_else(|| error("a list of whole amounts"))?
+                }
+                "blinds_or_straddles" => {
+                    hand.blinds_or_straddles =
+                        amounts(&value).ok_or_else(|| error("a list of whole amounts"))?
+                }
+                "min_bet" => {
+                    hand.min_bet = amount(&value).ok_or_else(|| error("a whole amount"))?
+                }
+                "starting_stacks" => {
+                    hand.starting_stacks =
+                        amounts(&value).ok_or_else(|| error("a list of whole amounts"))?
+                }
+                "actions" => {
+                    let PhhValue::Array(lines) = &value else {
+                        return Err(error("a list of strings"));
+                    };
+                    for line in lines {
+                        let line = string(line).ok_or_else(|| error("a list of strings"))?;
+                        hand.actions.push(PhhAction::parse(&line)?);
+                    }
+                }
+                "players" => {
+                    let PhhValue::Array(names) = &value else {
+                        return Err(error("a list of strings"));
+                    };
+                    let names = names.iter().map(string).collect::<Option<Vec<String>>>();
+                    hand.players = Some(names.ok_or_else(|| error("a list of strings"))?);
+                }
+                _ => hand.extra.push((key, value)),
+            }
+        }
+
+        if let Some((key, _)) = required.iter().find(|(_, seen)| !seen) {
+            return Err(format!("{key} is missing"));
+        }
+        let players = hand.starting_stacks.len();
+        for (key, len) in [
+            ("antes", hand.antes.len()),
+            ("blinds_or_straddles", hand.blinds_or_straddles.len()),
+        ] {
+            if len != players {
+                return Err(format!("{key} has {len} entries for {players} players"));
+            }
+        }
+
+        Ok(hand)
+    }
+
+    /// Writes the hand as a `.phh` file. The known fields come first, then `extra` in order.
+    /// Errors on a NaN or infinite float in `extra`, or an amount bigger than `i64::MAX`.
+    pub fn to_phh(&self) -> Result<String, String> {
+        if let Some((key, _)) = self.extra.iter().find(|(_, value)| has_non_finite(value)) {
+            return Err(format!("{key} is not a finite number"));
+        }
+
+        let integer = |key: &str, amount: u64| {
+            i64::try_from(amount)
+                .map(PhhValue::Integer)
+                .map_err(|_| format!("{amount} in {key} does not fit in a PHH integer"))
+        };
+        let list = |key: &str, amounts: &[u64]| {
+            let values = amounts
+                .iter()
+                .map(|&amount| integer(key, amount))
+                .collect::<Result<_, _>>()?;
+            Ok::<_, String>(PhhValue::Array(values))
+        };
+
+        let mut out = String::new();
+        let _ = writeln!(out, "variant = {}", PhhValue::String(self.variant.clone()));
+        let _ = writeln!(out, "antes = {}", list("antes", &self.antes)?);
+        let _ = writeln!(
+            out,
+            "blinds_or_straddles = {}",
+            list("blinds_or_straddles", &self.blinds_or_straddles)?
+        );
+        let _ = writeln!(out, "min_bet = {}", integer("min_bet", self.min_bet)?);
+        let _ = writeln!(
+            out,
+            "starting_stacks = {}",
+            list("starting_stacks", &self.starting_stacks)?
+        );
+        out.push_str("actions = [\n");
+        for action in &self.actions {
+            let _ = writeln!(out, "  {},", PhhValue::String(action.to_string()));
+        }
+        out.push_str("]\n");
+        if let Some(players) = &self.players {
+            let names = players.iter().cloned().map(PhhValue::String).collect();
+            let _ = writeln!(out, "players = {}", PhhValue::Array(names));
+        }
+        for (key, value) in &self.extra {
+            let _ = write_key(&mut out, key);
+            let _ = writeln!(out, " = {value}");
+        }
+
+        Ok(out)
+    }
+
+    /// Converts a hand played on a `HoldemGame` into a no-limit hold'em PHH hand.
+    pub fn from_history(history: &HandHistory) -> Self {
+        let seats = history.starting_stacks.len();
+        // PHH players start left of the button, so the button is the last one.
+        let order: Vec<usize> = (1..=seats)
+            .map(|offset| (history.button + offset) % seats)
+            .filter(|&seat| !history.hole_cards[seat].is_empty())
+            .collect();
+        let player_of = |seat: usize| {
+            order
+                .iter()
+                .position(|&s| s == seat)
+                .expect("Seat was dealt in")
+                + 1
+        };
+
+        let config = history.config;
+        let stacks: Vec<u64> = order
+            .iter()
+            .map(|&seat| history.starting_stacks[seat] as u64)
+            .collect();
+        let antes: Vec<u64> = stacks
+            .iter()
+            .map(|&stack| stack.min(config.ante as u64))
+            .collect();
+        let mut blinds = vec![0; order.len()];
+        let blind_players: &[usize] = if order.len() == 2 { &[1, 0] } else { &[0, 1] };
+        for (&player, blind) in blind_players
+            .iter()
+            .zip([config.small_blind, config.big_blind])
+        {
+            blinds[player] = (blind as u64).min(stacks[player] - antes[player]);
+        }
+
+        let mut actions: Vec<PhhAction> = order
+            .iter()
+            .map(|&seat| PhhAction::DealHole {
+                player: player_of(seat),
+                cards: history.hole_cards[seat].iter().copied().map(Some).collect(),
+            })
+            .collect();
+
+        // The board is dealt before the first action of each street, and what is left of it
+        // before the showdown when the players were all in.
+        let mut street = Street::Preflop;
+        let mut deal_board_until = |until: Street, actions: &mut Vec<PhhAction>| {
+            for (next, range) in [
+                (Street::Flop, 0..3),
+                (Street::Turn, 3..4),
+                (Street::River, 4..5),
+            ] {
+                if street < next
+                    && next <= until
+                    && let Some(cards) = history.board.get(range)
+                {
+                    actions.push(PhhAction::DealBoard(cards.to_vec()));
+                    street = next;
+                }
+            }
+        };
+
+        for recorded in &history.actions {
+            deal_board_until(recorded.street, &mut actions);
+            let player = player_of(recorded.seat);
+            actions.push(match recorded.action {
+                Action::Fold => PhhAction::Fold(player),
+                Action::Check | Action::Call => PhhAction::CheckOrCall(player),
+                Action::Bet(amount) | Action::Raise(amount) => PhhAction::CompleteBetOrRaiseTo {
+                    player,
+                    amount: amount as u64,
+                },
+            });
+        }
+        if !history.showdown.is_empty() {
+            deal_board_until(Street::River, &mut actions);
+        }
+        for shown in &history.showdown {
+            actions.push(PhhAction::ShowOrMuck {
+                player: player_of(shown.seat),
+                cards: Some(shown.hole_cards.iter().copied().map(Some).collect()),
+            });
+        }
+
+        PhhHand {
+            variant: "NT".to_string(),
+            antes,
+            blinds_or_straddles: blinds,
+            min_bet: config.big_blind as u64,
+            starting_stacks: stacks,
+            actions,
+            players: None,
+            extra: Vec::new(),
+        }
+    }
+
+    pub fn board(&self) -> Vec<Card> {
+        self.actions
+            .iter()
+            .filter_map(|action| match action {
+                PhhAction::DealBoard(cards) => Some(cards.clone()),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// The best five card hand of every player that showed their cards, by player number.
+    pub fn shown_hands(&self) -> Result<Vec<(usize, PokerHand)>, String> {
+        let board = self.board();
+        let mut hands = Vec::new();
+
+        for action in &self.actions {
+            if let PhhAction::ShowOrMuck {
+                player,
+                cards: Some(cards),
+            } = action
+            {
+                let mut all: Vec<Card> = cards.iter().flatten().copied().collect();
+                all.extend(&board);
+                if all.len() >= 5 {
+                    hands.push((*player, PokerHand::best_of(&all)?));
+                }
+            }
+        }
+
+        Ok(hands)
+    }
+}
+
+fn string(value: &PhhValue) -> Option<String> {
+    match value {
+        PhhValue::String(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+fn amount(value: &PhhValue) -> Option<u64> {
+    match value {
+        PhhValue::Integer(i) => u64::try_from(*i).ok(),
+        PhhValue::Float(x) if x.fract() == 0.0 && *x >= 0.0 => Some(*x as u64),
+        _ => None,
+    }
+}
+
+fn amounts(value: &PhhValue) -> Option<Vec<u64>> {
+    match value {
+        PhhValue::Array(values) => values.iter().map(amount).collect(),
+        _ => None,
+    }
+}
+
+// Reads the part of TOML that PHH files use: `key = value` pairs with strings, numbers,
+// booleans and (nested, multi-line) arrays. Tables are not supported.
+fn parse_toml(text: &str) -> Result<Vec<(String, PhhValue)>, String> {
+    let mut parser = TomlParser {
+        chars: text.chars().collect(),
+        position: 0,
+        line: 1,
+    };
+    let mut pairs: Vec<(String, PhhValue)> = Vec::new();
+
+    loop {
+        parser.skip_blank(true);
+        let Some(c) = parser.peek() else {
+            return Ok(pairs);
+        };
+        if c == '[' {
+            return Err(parser.error("tables are not supported in PHH files"));
+        }
+
+        let key = parser.key()?;
+        parser.skip_blank(false);
+        if parser.next() != Some('=') {
+            return Err(parser.error(&format!("expected `=` after {key}")));
+        }
+        parser.skip_blank(false);
+        let value = parser.value()?;
+        if pairs.iter().any(|(existing, _)| *existing == key) {
+            return Err(parser.error(&format!("{key} is set twice")));
+        }
+        pairs.push((key, value));
+
+        parser.skip_blank(false);
+        match parser.peek() {
+            None | Some('\n') => {}
+            Some(c) => return Err(parser.error(&format!("unexpected {c:?} after a value"))),
+        }
+    }
+}
+
+struct TomlParser {
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+}
+
+impl TomlParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("Line {}: {message}", self.line)
+    }
+
+    // Skips spaces and comments, and new lines too when `newlines` is set.
+    fn skip_blank(&mut self, newlines: bool) {
+        while let Some(c) = self.peek() {
+            match c {
+                ' ' | '\t' | '\r' => {}
+                '\n' if newlines => {}
+                '#' => {
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.next();
+                    }
+                    continue;
+                }
+                _ => return,
+            }
+            self.next();
+        }
+    }
+
+    fn key(&mut self) -> Result<String, String> {
+        if self.peek() == Some('"') {
+            return self.string();
+        }
+
+        let mut key = String::new();
+        while let Some(c) = self
+            .peek()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+        {
+            key.push(c);
+            self.next();
+        }
+        if key.is_empty() {
+            return Err(self.error("expected a key"));
+        }
+        Ok(key)
+    }
+
+    fn value(&mut self) -> Result<PhhValue, String> {
+        match self.peek() {
+            Some('"') | Some('\'') => self.string().map(PhhValue::String),
+            Some('[') => {
+                self.next();
+                let mut values = Vec::new();
+                loop {
+                    self.skip_blank(true);
+                    if self.peek() == Some(']') {
+                        self.next();
+                        return Ok(PhhValue::Array(values));
+                    }
+                    values.push(self.value()?);
+                    self.skip_blank(true);
+                    match self.next() {
+                        Some(',') => {}
+                        Some(']') => return Ok(PhhValue::Array(values)),
+                        _ => return Err(self.error("expected `,` or `]` in an array")),
+                    }
+                }
+            }
+            Some(_) => {
+                let mut word = String::new();
+                while let Some(c) = self
+                    .peek()
+                    .filter(|c| !matches!(c, ',' | ']' | '#') && !c.is_whitespace())
+                {
+                    word.push(c);
+                    self.next();
+                }
+
+                match word.as_str() {
+                    "true" => Ok(PhhValue::Boolean(true)),
+                    "false" => Ok(PhhValue::Boolean(false)),
+                    _ => {
+                        let number = word.replace('_', "");
+                        if let Ok(i) = number.parse() {
+                            Ok(PhhValue::Integer(i))
+                        } else if let Ok(x) = number.parse() {
+                            Ok(PhhValue::Float(x))
+                        } else {
+                            Err(self.error(&format!("{word:?} is not a value")))
+                        }
+                    }
+                }
+            }
+            None => Err(self.error("expected a value")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let quote = self.next().expect("Called on a quote");
+        let mut s = String::new();
+
+        loop {
+            match self.next() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some(c) if c == quote => return Ok(s),
+                Some('\\') if quote == '"' => match self.next() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some('u') => {
+                        let hex: String = (0..4).filter_map(|_| self.next()).collect();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| self.error(&format!("bad escape \\u{hex}")))?;
+                        s.push(c);
+                    }
+                    other => return Err(self.error(&format!("bad escape {other:?}"))),
+                },
+                Some(c) => s.push(c),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PokerHandType;
+    use crate::deck::Deck;
+    use crate::holdem::{HoldemConfig, HoldemGame};
+
+    const THREE_WAY: &str = include_str!("../data/phh/three_way_all_in.phh");
+    const HEADS_UP: &str = include_str!("../data/phh/heads_up_unknown_cards.phh");
+
+    #[test]
+    fn phh_parse() {
+        let hand = PhhHand::parse(THREE_WAY).unwrap();
+        assert_eq!(hand.variant, "NT");
+        assert_eq!(hand.antes, vec![500; 3]);
+        assert_eq!(hand.blinds_or_straddles, vec![1000, 2000, 0]);
+        assert_eq!(hand.min_bet, 2000);
+        assert_eq!(hand.starting_stacks, vec![1125600, 2000000, 553500]);
+        assert_eq!(hand.actions.len(), 19);
+        assert_eq!(
+            hand.actions[3],
+            PhhAction::CompleteBetOrRaiseTo {
+                player: 3,
+                amount: 7000
+            }
+        );
+        assert_eq!(hand.actions[10], PhhAction::Fold(2));
+        assert_eq!(
+            hand.players,
+            Some(vec![
+                "Alice".to_string(),
+                "Bob".to_string(),
+                "Carol".to_string()
+            ])
+        );
+        assert_eq!(
+            hand.extra,
+            vec![
+                ("ante_trimming_status".to_string(), PhhValue::Boolean(true)),
+                ("hand".to_string(), PhhValue::Integer(1)),
+                (
+                    "finishing_stacks".to_string(),
+                    PhhValue::Array(vec![
+                        PhhValue::Integer(1702600),
+                        PhhValue::Integer(1976500),
+                        PhhValue::Integer(0),
+                    ])
+                ),
+            ]
+        );
+
+        let board: Vec<String> = hand.board().iter().map(Card::to_string).collect();
+        assert_eq!(board, ["Jc", "3d", "5c", "Ts", "Jd"]);
+
+        let shown = hand.shown_hands().unwrap();
+        assert_eq!(shown.len(), 2);
+        assert_eq!(shown[0].0, 1);
+        assert_eq!(shown[0].1.poker_hand_type, Some(PokerHandType::OnePair));
+        assert_eq!(shown[1].0, 3);
+        assert!(shown[0].1 > shown[1].1, "Ace kicker beats the seven");
+
+        let hand = PhhHand::parse(HEADS_UP).unwrap();
+        assert_eq!(
+            hand.actions[0].to_string(),
+            "d dh p1 ????",
+            "Unknown cards are kept"
+        );
+        assert_eq!(
+            hand.extra[0],
+            (
+                "table".to_string(),
+                PhhValue::String("Heads-up \"deep\" 1/2".to_string())
+            )
+        );
+        assert_eq!(
+            hand.extra[1],
+            ("time_zone_offset".to_string(), PhhValue::Float(-3.5))
+        );
+        assert!(hand.shown_hands().unwrap().is_empty());
+    }
+
+    #[test]
+    fn phh_round_trip() {
+        for text in [THREE_WAY, HEADS_UP] {
+            let hand = PhhHand::parse(text).unwrap();
+            let written = hand.to_phh().unwrap();
+            let again = PhhHand::parse(&written).unwrap();
+
+            assert_eq!(again, hand);
+            assert_eq!(again.to_phh().unwrap(), written);
+        }
+
+        let mut hand = PhhHand::parse(HEADS_UP).unwrap();
+        let awkward = "Tab\there, \u{1b}[1mbold\u{1b}[0m, \u{7f}, 'q' \"qq\" \\ é ♠";
+        hand.extra = vec![
+            ("event".to_string(), PhhValue::String(awkward.to_string())),
+            ("rake".to_string(), PhhValue::Float(1e-7)),
+            ("time zone \"UTC\"".to_string(), PhhValue::Integer(0)),
+        ];
+        let written = hand.to_phh().unwrap();
+        assert!(
+            written.contains(
+                r#"event = "Tab\there, \u001B[1mbold\u001B[0m, \u007F, 'q' \"qq\" \\ é ♠""#
+            ),
+            "{written}"
+        );
+        assert!(written.contains("\nrake = 1e-7\n"), "{written}");
+        assert!(written.contains(r#""time zone \"UTC\"" = 0"#), "{written}");
+        assert_eq!(PhhHand::parse(&written).unwrap(), hand);
+
+        for x in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            hand.extra = vec![(
+                "odds".to_string(),
+                PhhValue::Array(vec![PhhValue::Float(1.5), PhhValue::Float(x)]),
+            )];
+            assert_eq!(
+                hand.to_phh(),
+                Err("odds is not a finite number".to_string())
+            );
+        }
+        assert_eq!(PhhValue::Float(f64::NEG_INFINITY).to_string(), "-inf");
+
+        hand.extra.clear();
+        hand.starting_stacks[1] = i64::MAX as u64 + 1;
+        assert_eq!(
+            hand.to_phh(),
+            Err("9223372036854775808 in starting_stacks does not fit in a PHH integer".to_string())
+        );
+        hand.starting_stacks[1] = i64::MAX as u64;
+        assert_eq!(PhhHand::parse(&hand.to_phh().unwrap()).unwrap(), hand);
+    }
+
+    #[test]
+    fn phh_from_history() {
+        let config = HoldemConfig {
+            small_blind: 1,
+            big_blind: 2,
+            ante: 0,
+        };
+        let mut game = HoldemGame::new(&[100, 80, 120], config).unwrap();
+        game.start_hand(Deck::shuffled(7)).unwrap();
+        game.act(Action::Raise(6)).unwrap();
+        game.act(Action::Call).unwrap();
+        game.act(Action::Call).unwrap();
+        game.act(Action::Bet(74)).unwrap();
+        game.act(Action::Call).unwrap();
+        game.act(Action::Call).unwrap();
+        while !game.is_hand_over() {
+            game.act(Action::Check).unwrap();
+        }
+        let history = game.history();
+
+        let hand = PhhHand::parse(&PhhHand::from_history(history).to_phh().unwrap()).unwrap();
+        // Seat 1 is left of the button at seat 0, so it is p1 and the button is p3.
+        assert_eq!(hand.starting_stacks, vec![80, 120, 100]);
+        assert_eq!(hand.blinds_or_straddles, vec![1, 2, 0]);
+        assert_eq!(hand.min_bet, 2);
+        assert_eq!(
+            hand.actions[0],
+            PhhAction::DealHole {
+                player: 1,
+                cards: history.hole_cards[1].iter().copied().map(Some).collect(),
+            }
+        );
+        assert_eq!(hand.actions[3].to_string(), "p3 cbr 6");
+        assert!(matches!(hand.actions[6], PhhAction::DealBoard(ref flop) if flop.len() == 3));
+        assert_eq!(hand.actions[7].to_string(), "p1 cbr 74");
+
+        let board: Vec<String> = hand.board().iter().map(Card::to_string).collect();
+        let recorded: Vec<String> = history.board.iter().map(Card::to_string).collect();
+        assert_eq!(board, recorded);
+
+        let shown = hand.shown_hands().unwrap();
+        assert_eq!(shown.len(), history.showdown.len());
+        for ((player, poker_hand), recorded) in shown.iter().zip(&history.showdown) {
+            assert_eq!(*player, (recorded.seat + 2) % 3 + 1);
+            assert_eq!(
+                poker_hand.poker_hand_type.as_ref(),
+                Some(&recorded.hand_type)
+            );
+        }
+
+        // Heads-up the button posts the small blind and is still the last player.
+        let mut game = HoldemGame::new(&[50, 50], config).unwrap();
+        game.start_hand(Deck::shuffled(1)).unwrap();
+        game.act(Action::Fold).unwrap();
+        let hand = PhhHand::from_history(game.history());
+        assert_eq!(hand.blinds_or_straddles, vec![2, 1]);
+        assert_eq!(hand.actions[2], PhhAction::Fold(2));
+    }
+
+    #[test]
+    fn phh_errors() {
+        let cases = [
+            ("variant = \"NT\"", "antes is missing"),
+            ("[table]\n", "Line 1: tables are not supported in PHH files"),
+            (
+                "variant = \"NT\"\nvariant = \"FT\"",
+                "Line 2: variant is set twice",
+            ),
+            (
+                "actions = [\n  \"p1 cc\",\n  \"p1 zz\"\n",
+                "Line 4: expected `,` or `]` in an array",
+            ),
+            (
+                "actions = [\"p1 zz\"]",
+                "\"p1 zz\" is not a supported action",
+            ),
+            (
+                "actions = [\"d dh p0 AhKh\"]",
+                "\"p0\" is not a player in \"d dh p0 AhKh\"",
+            ),
+            ("min_bet = 2.5", "min_bet must be a whole amount"),
+            ("variant = \"NT", "Line 1: unterminated string"),
+        ];
+
+        for (text, expected) in cases {
+            assert_eq!(PhhHand::parse(text), Err(expected.to_string()), "{text:?}");
+        }
+
+        let mut text = THREE_WAY.replace("antes = [500, 500, 500]", "antes = [500, 500]");
+        assert_eq!(
+            PhhHand::parse(&text),
+            Err("antes has 2 entries for 3 players".to_string())
+        );
+        text = THREE_WAY.replace("Jc3d5c", "Jc3d5x");
+        assert!(PhhHand::parse(&text).is_err());
+    }
+}
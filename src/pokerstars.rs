@@ -0,0 +1,855 @@
+use crate::holdem::Street;
+use crate::pot::{award_pots, side_pots};
+use crate::{Card, PokerHand, PokerHandType, parse_cards};
+
+/// A hand imported from a PokerStars text hand history. Amounts are in hundredths of the
+/// currency (cents for cash games, 1/100 of a chip for tournaments) so both fit in a `u64`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PokerStarsHand {
+    pub hand_id: String,
+    /// Game and stakes as written in the header, e.g. `Hold'em No Limit ($0.01/$0.02 USD)`.
+    pub game: String,
+    pub table: String,
+    pub button_seat: u32,
+    pub seats: Vec<PokerStarsSeat>,
+    pub board: Vec<Card>,
+    pub actions: Vec<PokerStarsAction>,
+    pub showdown: Vec<PokerStarsShowdown>,
+    /// Who collected how much, as reported by the site.
+    pub collected: Vec<(String, u64)>,
+    pub total_pot: Option<u64>,
+    pub rake: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PokerStarsSeat {
+    pub number: u32,
+    pub player: String,
+    pub stack: u64,
+    /// Known when dealt to the hero or shown down.
+    pub hole_cards: Option<Vec<Card>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PokerStarsActionKind {
+    Ante(u64),
+    SmallBlind(u64),
+    BigBlind(u64),
+    /// The small blind part of `posts small & big blinds`. It goes in the pot, like an ante, but
+    /// does not count toward what the player has to call.
+    DeadSmallBlind(u64),
+    Fold,
+    Check,
+    Call(u64),
+    Bet(u64),
+    /// Raised by `by` for a street total of `to`.
+    Raise {
+        by: u64,
+        to: u64,
+    },
+    UncalledBetReturned(u64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PokerStarsAction {
+    pub street: Street,
+    pub player: String,
+    pub kind: PokerStarsActionKind,
+    pub all_in: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PokerStarsShowdown {
+    pub player: String,
+    pub cards: Vec<Card>,
+    /// The site's description, e.g. `three of a kind, Nines`.
+    pub description: String,
+    pub site_hand_type: Option<PokerHandType>,
+}
+
+/// Somewhere our evaluation does not agree with what the site reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Disagreement {
+    HandType {
+        player: String,
+        site: PokerHandType,
+        evaluated: PokerHandType,
+    },
+    Winners {
+        site: Vec<String>,
+        evaluated: Vec<String>,
+    },
+}
+
+/// Splits a file of hand histories and parses every hand in it.
+pub fn parse_hands(text: &str) -> Result<Vec<PokerStarsHand>, String> {
+    let mut hands = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut first_line = 1;
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim_start_matches('\u{feff}');
+        if line.starts_with("PokerStars ") && !current.is_empty() {
+            hands.push(parse_hand_lines(&current, first_line)?);
+            current.clear();
+        }
+        if current.is_empty() {
+            first_line = i + 1;
+        }
+        if !line.trim().is_empty() || !current.is_empty() {
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        hands.push(parse_hand_lines(&current, first_line)?);
+    }
+
+    Ok(hands)
+}
+
+pub fn parse_hand(text: &str) -> Result<PokerStarsHand, String> {
+    let lines: Vec<&str> = text.lines().collect();
+    parse_hand_lines(&lines, 1)
+}
+
+fn parse_hand_lines(lines: &[&str], first_line: usize) -> Result<PokerStarsHand, String> {
+    let mut hand = PokerStarsHand::default();
+    let mut street = Street::Preflop;
+    let mut in_summary = false;
+
+    for (i, line) in lines.iter().enumerate() {
+        let line = line.trim();
+        let error = |message: String| format!("Line {}: {message}", first_line + i);
+
+        if line.is_empty() {
+            continue;
+        }
+        if i == 0 {
+            parse_header(line, &mut hand).map_err(error)?;
+            continue;
+        }
+
+        if let Some(section) = line.strip_prefix("*** ") {
+            match section.split(" ***").next().unwrap_or_default() {
+                "HOLE CARDS" => street = Street::Preflop,
+                "FLOP" => street = Street::Flop,
+                "TURN" => street = Street::Turn,
+                "RIVER" => street = Street::River,
+                "SHOW DOWN" => street = Street::Showdown,
+                "SUMMARY" => in_summary = true,
+                _ => {}
+            }
+            continue;
+        }
+
+        if in_summary {
+            if let Some(rest) = line.strip_prefix("Total pot ") {
+                let mut parts = rest.split(" | ");
+                let total = parts.next().unwrap_or_default();
+                hand.total_pot = Some(parse_amount(first_word(total)).map_err(error)?);
+                for part in parts {
+                    if let Some(rake) = part.strip_prefix("Rake ") {
+                        hand.rake = Some(parse_amount(rake).map_err(error)?);
+                    }
+                }
+            } else if let Some(board) = line.strip_prefix("Board ") {
+                hand.board = bracketed_cards(board).map_err(error)?;
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("Table '") {
+            let (table, rest) = rest
+                .split_once('\'')
+                .ok_or_else(|| error("unterminated table name".to_string()))?;
+            hand.table = table.to_string();
+            let button = rest
+                .split_once("Seat #")
+                .and_then(|(_, rest)| rest.split_whitespace().next())
+                .ok_or_else(|| error("missing button seat".to_string()))?;
+            hand.button_seat = button
+                .parse()
+                .map_err(|_| error(format!("{button:?} is not a seat number")))?;
+            continue;
+        }
+
+        if street == Street::Preflop
+            && hand.actions.is_empty()
+            && let Some(rest) = line.strip_prefix("Seat ")
+        {
+            hand.seats.push(parse_seat(rest).map_err(error)?);
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("Dealt to ") {
+            // Other players' cards are only listed when known, e.g. `Dealt to Villain`.
+            if let Some((player, cards)) = rest.rsplit_once(" [") {
+                let cards = bracketed_cards(&format!("[{cards}")).map_err(error)?;
+                seat_mut(&mut hand, player).map_err(error)?.hole_cards = Some(cards);
+            }
+            continue;
+        }
+
+        if let Some((player, rest)) = line.rsplit_once(" collected ") {
+            if let Some(player) = known_player(&hand, player) {
+                let amount = parse_amount(first_word(rest)).map_err(error)?;
+                hand.collected.push((player, amount));
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("Uncalled bet (") {
+            let (amount, player) = rest
+                .split_once(") returned to ")
+                .ok_or_else(|| error("malformed uncalled bet".to_string()))?;
+            hand.actions.push(PokerStarsAction {
+                street,
+                player: player.to_string(),
+                kind: PokerStarsActionKind::UncalledBetReturned(
+                    parse_amount(amount).map_err(error)?,
+                ),
+                all_in: false,
+            });
+            continue;
+        }
+
+        let Some((player, rest)) = split_player(&hand, line) else {
+            // Chat, sitting out, disconnects and the like.
+            continue;
+        };
+
+        if let Some(shown) = rest.strip_prefix("shows ") {
+            let (cards, description) = shown.split_once(" (").unwrap_or((shown, ""));
+            let cards = bracketed_cards(cards).map_err(error)?;
+            let description = description.trim_end_matches(')').to_string();
+            seat_mut(&mut hand, &player).map_err(error)?.hole_cards = Some(cards.clone());
+            hand.showdown.push(PokerStarsShowdown {
+                site_hand_type: site_hand_type(&description),
+                player,
+                cards,
+                description,
+            });
+            continue;
+        }
+
+        if let Some(posted) = rest.strip_prefix("posts small & big blinds ") {
+            // A dead small blind plus the big blind, only the big blind plays.
+            let posted = parse_amount(first_word(posted)).map_err(error)?;
+            let big_blind = hand
+                .actions
+                .iter()
+                .find_map(|action| match action.kind {
+                    PokerStarsActionKind::BigBlind(amount) => Some(amount),
+                    _ => None,
+                })
+                .filter(|&big_blind| big_blind <= posted)
+                .ok_or_else(|| {
+                    error("small & big blinds without a big blind to match".to_string())
+                })?;
+            for kind in [
+                PokerStarsActionKind::DeadSmallBlind(posted - big_blind),
+                PokerStarsActionKind::BigBlind(big_blind),
+            ] {
+                hand.actions.push(PokerStarsAction {
+                    street,
+                    player: player.clone(),
+                    kind,
+                    all_in: rest.ends_with("and is all-in"),
+                });
+            }
+            continue;
+        }
+
+        if let Some(kind) = parse_action(rest).map_err(error)? {
+            hand.actions.push(PokerStarsAction {
+                street,
+                player,
+                kind,
+                all_in: rest.ends_with("and is all-in"),
+            });
+        }
+    }
+
+    if hand.seats.is_empty() {
+        return Err(format!("Hand starting on line {first_line} has no seats"));
+    }
+
+    Ok(hand)
+}
+
+fn parse_header(line: &str, hand: &mut PokerStarsHand) -> Result<(), String> {
+    let rest = line
+        .strip_prefix("PokerStars ")
+        .ok_or_else(|| format!("{line:?} is not a PokerStars hand header"))?;
+    let (id, rest) = rest
+        .split_once('#')
+        .and_then(|(_, rest)| rest.split_once(':'))
+        .ok_or_else(|| "missing hand number".to_string())?;
+    hand.hand_id = id.trim().to_string();
+
+    // Tournament headers put the tournament number and buy-in in front of the game.
+    let game = rest.split(" - ").next().unwrap_or_default().trim();
+    hand.game = match game.split_once(", ") {
+        Some((tournament, game)) if tournament.starts_with("Tournament") => game.to_string(),
+        _ => game.to_string(),
+    };
+
+    Ok(())
+}
+
+fn parse_seat(rest: &str) -> Result<PokerStarsSeat, String> {
+    let (number, rest) = rest
+        .split_once(": ")
+        .ok_or_else(|| format!("malformed seat {rest:?}"))?;
+    let (player, chips) = rest
+        .rsplit_once(" (")
+        .ok_or_else(|| format!("missing chips for seat {number}"))?;
+
+    Ok(PokerStarsSeat {
+        number: number
+            .parse()
+            .map_err(|_| format!("{number:?} is not a seat number"))?,
+        player: player.to_string(),
+        stack: parse_amount(first_word(chips))?,
+        hole_cards: None,
+    })
+}
+
+fn parse_action(rest: &str) -> Result<Option<PokerStarsActionKind>, String> {
+    let rest = rest.trim_end_matches(" and is all-in");
+    let mut words = rest.split_whitespace();
+
+    let kind = match words.next().unwrap_or_default() {
+        "folds" => PokerStarsActionKind::Fold,
+        "checks" => PokerStarsActionKind::Check,
+        "calls" => PokerStarsActionKind::Call(parse_amount(words.next().unwrap_or_default())?),
+        "bets" => PokerStarsActionKind::Bet(parse_amount(words.next().unwrap_or_default())?),
+        "raises" => {
+            let by = parse_amount(words.next().unwrap_or_default())?;
+            let to = match (words.next(), words.next()) {
+                (Some("to"), Some(to)) => parse_amount(to)?,
+                _ => return Err(format!("malformed raise {rest:?}")),
+            };
+            PokerStarsActionKind::Raise { by, to }
+        }
+        "posts" => {
+            let amount = parse_amount(rest.split_whitespace().last().unwrap_or_default())?;
+            if rest.starts_with("posts small blind") {
+                PokerStarsActionKind::SmallBlind(amount)
+            } else if rest.starts_with("posts big blind") {
+                PokerStarsActionKind::BigBlind(amount)
+            } else if rest.starts_with("posts the ante") {
+                PokerStarsActionKind::Ante(amount)
+            } else {
+                return Err(format!("unknown post {rest:?}"));
+            }
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(kind))
+}
+
+fn site_hand_type(description: &str) -> Option<PokerHandType> {
+    let description = description.to_lowercase();
+    let hand_types = [
+        ("high card", PokerHandType::HighCard),
+        ("a pair", PokerHandType::OnePair),
+        ("two pair", PokerHandType::TwoPair),
+        ("three of a kind", PokerHandType::ThreeOfAKind),
+        ("a straight flush", PokerHandType::StraightFlush),
+        ("a straight", PokerHandType::Straight),
+        ("a flush", PokerHandType::Flush),
+        ("a full house", PokerHandType::FullHouse),
+        ("four of a kind", PokerHandType::FourOfAKind),
+        ("a royal flush", PokerHandType::RoyalFlush),
+    ];
+
+    hand_types
+        .into_iter()
+        .find(|(prefix, _)| description.starts_with(prefix))
+        .map(|(_, hand_type)| hand_type)
+}
+
+/// Parses `$1,234.5`, `€0.02` or `1500` into hundredths.
+pub fn parse_amount(text: &str) -> Result<u64, String> {
+    let cleaned: String = text
+        .trim()
+        .trim_start_matches(['$', '€', '£'])
+        .chars()
+        .filter(|c| *c != ',')
+        .collect();
+    let (whole, fraction) = cleaned.split_once('.').unwrap_or((&cleaned, ""));
+    if fraction.len() > 2 || whole.is_empty() && fraction.is_empty() {
+        return Err(format!("{text:?} is not an amount"));
+    }
+
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole
+            .parse()
+            .map_err(|_| format!("{text:?} is not an amount"))?
+    };
+    let fraction: u64 = if fraction.is_empty() {
+        0
+    } else {
+        format!("{fraction:0<2}")
+            .parse()
+            .map_err(|_| format!("{text:?} is not an amount"))?
+    };
+
+    whole
+        .checked_mul(100)
+        .and_then(|cents| cents.checked_add(fraction))
+        .ok_or_else(|| format!("{text:?} is not an amount"))
+}
+
+fn first_word(text: &str) -> &str {
+    text.split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .trim_matches(|c| c == '(' || c == ')')
+}
+
+fn bracketed_cards(text: &str) -> Result<Vec<Card>, String> {
+    // `[2c 7h 9d] [Qs]` on the turn, only the last group is new. The summary has them all.
+    let last = text.rsplit('[').next().unwrap_or_default();
+    let all: Vec<&str> = text
+        .split('[')
+        .skip(1)
+        .map(|group| group.split(']').next().unwrap_or_default())
+        .collect();
+    if all.is_empty() || !last.contains(']') {
+        return Err(format!("{text:?} has no cards in brackets"));
+    }
+
+    parse_cards(&all.join(" "))
+}
+
+fn known_player(hand: &PokerStarsHand, name: &str) -> Option<String> {
+    hand.seats
+        .iter()
+        .find(|seat| seat.player == name)
+        .map(|seat| seat.player.clone())
+}
+
+// Player names can hold spaces and colons, so match against the seated names.
+fn split_player<'a>(hand: &PokerStarsHand, line: &'a str) -> Option<(String, &'a str)> {
+    hand.seats
+        .iter()
+        .filter_map(|seat| {
+            line.strip_prefix(seat.player.as_str())
+                .and_then(|rest| rest.strip_prefix(": "))
+                .map(|rest| (seat.player.clone(), rest))
+        })
+        .max_by_key(|(player, _)| player.len())
+}
+
+fn seat_mut<'a>(
+    hand: &'a mut PokerStarsHand,
+    player: &str,
+) -> Result<&'a mut PokerStarsSeat, String> {
+    hand.seats
+        .iter_mut()
+        .find(|seat| seat.player == player)
+        .ok_or_else(|| format!("{player:?} is not seated"))
+}
+
+impl PokerStarsHand {
+    /// What every seat put in over the hand, after uncalled bets were returned.
+    pub fn contributions(&self) -> Vec<u64> {
+        let mut total = vec![0; self.seats.len()];
+        let mut street_total = vec![0; self.seats.len()];
+        let mut street = Street::Preflop;
+
+        for action in &self.actions {
+            if action.street != street {
+                street = action.street;
+                street_total.iter_mut().for_each(|amount| *amount = 0);
+            }
+            let Some(seat) = self
+                .seats
+                .iter()
+                .position(|seat| seat.player == action.player)
+            else {
+                continue;
+            };
+
+            let added = match action.kind {
+                PokerStarsActionKind::Ante(amount)
+                | PokerStarsActionKind::DeadSmallBlind(amount) => {
+                    total[seat] += amount;
+                    continue;
+                }
+                PokerStarsActionKind::UncalledBetReturned(amount) => {
+                    total[seat] -= amount.min(total[seat]);
+                    street_total[seat] -= amount.min(street_total[seat]);
+                    continue;
+                }
+                PokerStarsActionKind::SmallBlind(amount)
+                | PokerStarsActionKind::BigBlind(amount)
+                | PokerStarsActionKind::Call(amount)
+                | PokerStarsActionKind::Bet(amount) => amount,
+                PokerStarsActionKind::Raise { to, .. } => to - street_total[seat].min(to),
+                PokerStarsActionKind::Fold | PokerStarsActionKind::Check => 0,
+            };
+            total[seat] += added;
+            street_total[seat] += added;
+        }
+
+        total
+    }
+
+    /// Re-evaluates every hand shown down with `set_hand_type` and re-awards the pots, and
+    /// lists where that disagrees with the site. Only Hold'em hands can be checked.
+    pub fn check(&self) -> Result<Vec<Disagreement>, String> {
+        if !self.game.contains("Hold'em") {
+            return Err(format!("Can only check Hold'em hands, not {:?}", self.game));
+        }
+
+        let mut disagreements = Vec::new();
+        let mut hands: Vec<Option<PokerHand>> = vec![None; self.seats.len()];
+
+        for shown in &self.showdown {
+            let cards: Vec<Card> = shown.cards.iter().chain(&self.board).copied().collect();
+            let best = if cards.len() >= 5 {
+                PokerHand::best_of(&cards)?
+            } else {
+                continue;
+            };
+            let evaluated = best
+                .poker_hand_type
+                .clone()
+                .expect("best_of sorts the hand");
+
+            if let Some(site) = &shown.site_hand_type
+                && *site != evaluated
+            {
+                disagreements.push(Disagreement::HandType {
+                    player: shown.player.clone(),
+                    site: site.clone(),
+                    evaluated,
+                });
+            }
+
+            if let Some(seat) = self
+                .seats
+                .iter()
+                .position(|seat| seat.player == shown.player)
+            {
+                hands[seat] = Some(best);
+            }
+        }
+
+        let folded: Vec<bool> = self
+            .seats
+            .iter()
+            .map(|seat| {
+                self.actions.iter().any(|action| {
+                    action.player == seat.player && action.kind == PokerStarsActionKind::Fold
+                })
+            })
+            .collect();
+        let contributions: Vec<u32> = self
+            .contributions()
+            .into_iter()
+            .map(|amount| {
+                u32::try_from(amount).map_err(|_| {
+                    format!("{amount} is more than a pot can hold, at most {}", u32::MAX)
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        let button = self
+            .seats
+            .iter()
+            .position(|seat| seat.number == self.button_seat)
+            .unwrap_or(0);
+
+        // Folded players never collect, and a hand nobody showed can only be uncontested.
+        let contested = folded.iter().filter(|folded| !**folded).count() > 1;
+        if !contested || hands.iter().any(Option::is_some) {
+            let awarded = award_pots(&side_pots(&contributions, &folded), &hands, button)?;
+            let mut evaluated: Vec<String> = awarded
+                .iter()
+                .zip(&self.seats)
+                .filter(|(won, _)| **won > 0)
+                .map(|(_, seat)| seat.player.clone())
+                .collect();
+            let mut site: Vec<String> = self
+                .collected
+                .iter()
+                .map(|(player, _)| player.clone())
+                .collect();
+            evaluated.sort();
+            site.sort();
+            site.dedup();
+
+            if evaluated != site {
+                disagreements.push(Disagreement::Winners { site, evaluated });
+            }
+        }
+
+        Ok(disagreements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAND: &str = "\
+PokerStars Hand #200000000001: Hold'em No Limit ($0.01/$0.02 USD) - 2020/01/01 12:00:00 ET
+Table 'Alpha II' 6-max Seat #1 is the button
+Seat 1: Hero ($2 in chips)
+Seat 2: Small: Blind ($2.13 in chips)
+Seat 3: Villain ($1.50 in chips)
+Small: Blind: posts small blind $0.01
+Villain: posts big blind $0.02
+*** HOLE CARDS ***
+Dealt to Hero [Ah Kh]
+Hero: raises $0.04 to $0.06
+Small: Blind: folds
+Villain: calls $0.04
+*** FLOP *** [2c 7h 9d]
+Villain: checks
+Hero: bets $0.08
+Villain: calls $0.08
+*** TURN *** [2c 7h 9d] [Qs]
+Villain: checks
+Hero: checks
+*** RIVER *** [2c 7h 9d Qs] [3c]
+Villain: bets $0.10
+Villain said, \"nh\"
+Hero: calls $0.10
+*** SHOW DOWN ***
+Villain: shows [9c 9h] (three of a kind, Nines)
+Hero: shows [Ah Kh] (high card Ace)
+Villain collected $0.47 from pot
+*** SUMMARY ***
+Total pot $0.49 | Rake $0.02
+Board [2c 7h 9d Qs 3c]
+Seat 1: Hero (button) showed [Ah Kh] and lost with high card Ace
+Seat 2: Small: Blind (small blind) folded before Flop
+Seat 3: Villain (big blind) showed [9c 9h] and won ($0.47) with three of a kind, Nines
+";
+
+    const ALL_IN_HAND: &str = "\
+PokerStars Hand #200000000002: Tournament #3000, $1+$0.10 USD Hold'em No Limit - Level I (10/20) - 2020/01/01 12:05:00 ET
+Table '3000 1' 9-max Seat #3 is the button
+Seat 1: Short (500 in chips)
+Seat 2: Deep (3000 in chips)
+Seat 3: Middle (1500 in chips)
+Short: posts the ante 5
+Deep: posts the ante 5
+Middle: posts the ante 5
+Short: posts small blind 10
+Deep: posts big blind 20
+*** HOLE CARDS ***
+Middle: raises 1475 to 1495 and is all-in
+Short: calls 485 and is all-in
+Deep: raises 1500 to 2995 and is all-in
+Uncalled bet (1500) returned to Deep
+*** FLOP *** [2c 7h 9d]
+*** TURN *** [2c 7h 9d] [Qs]
+*** RIVER *** [2c 7h 9d Qs] [3c]
+*** SHOW DOWN ***
+Short: shows [Ac Ad] (a pair of Aces)
+Deep: shows [Kc Kd] (a pair of Kings)
+Middle: shows [5c 5d] (a pair of Fives)
+Deep collected 2000 from side pot
+Short collected 1500 from main pot
+*** SUMMARY ***
+Total pot 3500 Main pot 1500. Side pot 2000. | Rake 0
+Board [2c 7h 9d Qs 3c]
+";
+
+    const DEAD_BLIND_HAND: &str = "\
+PokerStars Hand #200000000003: Hold'em No Limit ($0.01/$0.02 USD) - 2020/01/01 12:10:00 ET
+Table 'Alpha II' 6-max Seat #1 is the button
+Seat 1: Hero ($2 in chips)
+Seat 2: Small: Blind ($2.13 in chips)
+Seat 3: Villain ($1.50 in chips)
+Seat 4: Early ($2 in chips)
+Seat 5: Returning ($1 in chips)
+Small: Blind: posts small blind $0.01
+Villain: posts big blind $0.02
+Returning: posts small & big blinds $0.03
+*** HOLE CARDS ***
+Early: raises $0.04 to $0.06
+Returning: raises $0.12 to $0.18
+Hero: folds
+Small: Blind: folds
+Villain: folds
+Early: calls $0.12
+*** FLOP *** [2c 7h 9d]
+Early: checks
+Returning: bets $0.10
+Early: folds
+Uncalled bet ($0.10) returned to Returning
+Returning collected $0.40 from pot
+*** SUMMARY ***
+Total pot $0.40 | Rake $0
+Board [2c 7h 9d]
+";
+
+    #[test]
+    fn pokerstars_parse_hand() {
+        let hand = parse_hand(HAND).unwrap();
+
+        assert_eq!(hand.hand_id, "200000000001");
+        assert_eq!(hand.game, "Hold'em No Limit ($0.01/$0.02 USD)");
+        assert_eq!(hand.table, "Alpha II");
+        assert_eq!(hand.button_seat, 1);
+        assert_eq!(hand.seats.len(), 3);
+        assert_eq!(hand.seats[1].player, "Small: Blind");
+        assert_eq!(hand.seats[1].stack, 213);
+        assert_eq!(
+            hand.seats[0].hole_cards.as_ref().unwrap()[0].to_string(),
+            "Ah"
+        );
+        assert_eq!(hand.board.len(), 5);
+        assert_eq!(hand.actions.len(), 12);
+        assert_eq!(
+            hand.actions[2],
+            PokerStarsAction {
+                street: Street::Preflop,
+                player: "Hero".to_string(),
+                kind: PokerStarsActionKind::Raise { by: 4, to: 6 },
+                all_in: false,
+            }
+        );
+        assert_eq!(hand.actions[3].kind, PokerStarsActionKind::Fold);
+        assert_eq!(hand.actions[11].street, Street::River);
+        assert_eq!(
+            hand.showdown[0].site_hand_type,
+            Some(PokerHandType::ThreeOfAKind)
+        );
+        assert_eq!(hand.collected, vec![("Villain".to_string(), 47)]);
+        assert_eq!(hand.total_pot, Some(49));
+        assert_eq!(hand.rake, Some(2));
+        assert_eq!(hand.contributions(), vec![24, 1, 24]);
+        assert_eq!(hand.check().unwrap(), vec![]);
+    }
+
+    #[test]
+    fn pokerstars_side_pots() {
+        let hand = parse_hand(ALL_IN_HAND).unwrap();
+
+        assert_eq!(hand.game, "$1+$0.10 USD Hold'em No Limit");
+        assert_eq!(hand.contributions(), vec![50000, 150000, 150000]);
+        assert!(hand.actions[5].all_in);
+        assert_eq!(hand.check().unwrap(), vec![]);
+    }
+
+    #[test]
+    fn pokerstars_dead_small_blind() {
+        let hand = parse_hand(DEAD_BLIND_HAND).unwrap();
+
+        let posted: Vec<PokerStarsActionKind> = hand
+            .actions
+            .iter()
+            .filter(|action| action.player == "Returning")
+            .map(|action| action.kind)
+            .take(3)
+            .collect();
+        assert_eq!(
+            posted,
+            vec![
+                PokerStarsActionKind::DeadSmallBlind(1),
+                PokerStarsActionKind::BigBlind(2),
+                PokerStarsActionKind::Raise { by: 12, to: 18 },
+            ]
+        );
+        // Only the big blind counts toward the raise, the dead small blind comes on top.
+        assert_eq!(hand.contributions(), vec![0, 1, 2, 18, 19]);
+        assert_eq!(hand.check().unwrap(), vec![]);
+
+        let no_big_blind = DEAD_BLIND_HAND.replace("Villain: posts big blind $0.02\n", "");
+        let error = parse_hand(&no_big_blind).unwrap_err();
+        assert!(error.contains("without a big blind"), "{error}");
+    }
+
+    #[test]
+    fn pokerstars_check_rejects_huge_pots() {
+        let huge = DEAD_BLIND_HAND
+            .replace("Early ($2 in chips)", "Early ($50000000 in chips)")
+            .replace("Returning ($1 in chips)", "Returning ($50000000 in chips)")
+            .replace(
+                "Returning: bets $0.10\nEarly: folds\nUncalled bet ($0.10) returned to Returning",
+                "Returning: bets $45000000\nEarly: calls $45000000",
+            );
+        let hand = parse_hand(&huge).unwrap();
+
+        assert_eq!(hand.contributions()[4], 4_500_000_019);
+        let error = hand.check().unwrap_err();
+        assert!(error.starts_with("4500000018 is more"), "{error}");
+    }
+
+    #[test]
+    fn pokerstars_check_rejects_other_games() {
+        let omaha = HAND.replace("Hold'em No Limit", "Omaha Pot Limit");
+        let hand = parse_hand(&omaha).unwrap();
+        assert_eq!(
+            hand.check().unwrap_err(),
+            "Can only check Hold'em hands, not \"Omaha Pot Limit ($0.01/$0.02 USD)\""
+        );
+    }
+
+    #[test]
+    fn pokerstars_flags_disagreements() {
+        let wrong_type = HAND.replace(
+            "Villain: shows [9c 9h] (three of a kind, Nines)",
+            "Villain: shows [9c 9h] (a full house, Nines full of Twos)",
+        );
+        assert_eq!(
+            parse_hand(&wrong_type).unwrap().check().unwrap(),
+            vec![Disagreement::HandType {
+                player: "Villain".to_string(),
+                site: PokerHandType::FullHouse,
+                evaluated: PokerHandType::ThreeOfAKind,
+            }]
+        );
+
+        let wrong_winner = HAND.replace("Villain collected", "Hero collected");
+        assert_eq!(
+            parse_hand(&wrong_winner).unwrap().check().unwrap(),
+            vec![Disagreement::Winners {
+                site: vec!["Hero".to_string()],
+                evaluated: vec!["Villain".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn pokerstars_parse_hands() {
+        let file = format!("\u{feff}{HAND}\n\n\n{ALL_IN_HAND}\n\n");
+        let hands = parse_hands(&file).unwrap();
+        assert_eq!(hands.len(), 2);
+        assert_eq!(hands[1].hand_id, "200000000002");
+
+        let broken = format!("{HAND}\n\n{}", ALL_IN_HAND.replace("[Ac Ad]", "[Ac Ax]"));
+        let error = parse_hands(&broken).unwrap_err();
+        assert!(error.starts_with("Line 55:"), "{error}");
+    }
+
+    #[test]
+    fn pokerstars_parse_amount() {
+        let cases = vec![
+            ("$0.02", Ok(2)),
+            ("$2", Ok(200)),
+            ("$1,234.5", Ok(123450)),
+            ("€0.10", Ok(10)),
+            ("1500", Ok(150000)),
+            ("$0.001", Err(())),
+            ("abc", Err(())),
+            ("$184467440737095516.15", Ok(u64::MAX)),
+            ("$184467440737095516.16", Err(())),
+            ("$184467440737095517", Err(())),
+        ];
+
+        for (text, expected) in cases {
+            assert_eq!(parse_amount(text).map_err(|_| ()), expected, "Case: {text}");
+        }
+    }
+}
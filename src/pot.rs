@@ -0,0 +1,338 @@
+use std::cmp::Ordering;
+
+use crate::PokerHand;
+
+/// A main or side pot and the seats that can win it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pot {
+    pub amount: u32,
+    pub eligible: Vec<usize>,
+}
+
+/// Splits what every seat put in over the hand into a main pot and side pots.
+///
+/// Each all in amount of a player still in the hand caps a pot. Folded players' chips go into
+/// the pots they reached, but they are never eligible to win them. A bet no one called ends up
+/// in a pot of its own that only the bettor can win, which gives it back. If only folded players
+/// put chips in, they make one pot for everyone still in the hand.
+pub fn side_pots(contributions: &[u32], folded: &[bool]) -> Vec<Pot> {
+    assert_eq!(
+        contributions.len(),
+        folded.len(),
+        "Need a folded flag for every contribution"
+    );
+
+    let mut levels: Vec<u32> = contributions
+        .iter()
+        .zip(folded)
+        .filter(|(amount, folded)| !**folded && **amount > 0)
+        .map(|(amount, _)| *amount)
+        .collect();
+    levels.sort();
+    levels.dedup();
+    if levels.is_empty() && contributions.iter().any(|&amount| amount > 0) {
+        levels.push(0);
+    }
+
+    let mut pots = Vec::new();
+    let mut previous = 0;
+    for (i, &level) in levels.iter().enumerate() {
+        let last = i == levels.len() - 1;
+        let amount = contributions
+            .iter()
+            .map(|&amount| {
+                // Folded chips above the highest live amount still belong in the last pot.
+                let capped = if last { amount } else { amount.min(level) };
+                capped.saturating_sub(previous)
+            })
+            .sum();
+        let eligible = contributions
+            .iter()
+            .zip(folded)
+            .enumerate()
+            .filter(|(_, (amount, folded))| !**folded && **amount >= level)
+            .map(|(seat, _)| seat)
+            .collect();
+
+        pots.push(Pot { amount, eligible });
+        previous = level;
+    }
+
+    pots
+}
+
+/// Pays out every pot to the best eligible hands and returns what each seat won.
+///
+/// `hands` is indexed by seat, `None` for seats that did not show down. A pot with a single
+/// eligible seat goes to that seat without looking at hands. Split pots give the odd chips one
+/// at a time to the winners closest to the left of the button. Errors if a pot has no eligible
+/// seat with a hand, since nobody could take its chips.
+pub fn award_pots(
+    pots: &[Pot],
+    hands: &[Option<PokerHand>],
+    button: usize,
+) -> Result<Vec<u32>, &'static str> {
+    let seats = hands.len();
+    let mut winnings = vec![0; seats];
+
+    for pot in pots {
+        let winners: Vec<usize> = if pot.eligible.len() == 1 {
+            pot.eligible.clone()
+        } else {
+            let best = pot
+                .eligible
+                .iter()
+                .filter_map(|&seat| hands[seat].as_ref())
+                .max();
+            pot.eligible
+                .iter()
+                .copied()
+                .filter(|&seat| match (&hands[seat], best) {
+                    (Some(hand), Some(best)) => hand.cmp(best) == Ordering::Equal,
+                    _ => false,
+                })
+                .collect()
+        };
+        if winners.is_empty() {
+            return Err("No eligible hand to award a pot to");
+        }
+
+        let share = pot.amount / winners.len() as u32;
+        let mut odd_chips = pot.amount % winners.len() as u32;
+        for offset in 1..=seats {
+            let seat = (button + offset) % seats;
+            if winners.contains(&seat) {
+                winnings[seat] += share + u32::from(odd_chips > 0);
+                odd_chips = odd_chips.saturating_sub(1);
+            }
+        }
+    }
+
+    Ok(winnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::Rng;
+    use crate::{Card, Suit};
+
+    fn hand(cards: &[(u8, Suit)]) -> Option<PokerHand> {
+        let mut hand = PokerHand::new();
+        for &(rank, suit) in cards {
+            hand.add_card(Card::new(rank, suit).unwrap()).unwrap();
+        }
+        hand.sort_hand().unwrap();
+        Some(hand)
+    }
+
+    fn pot(amount: u32, eligible: &[usize]) -> Pot {
+        Pot {
+            amount,
+            eligible: eligible.to_vec(),
+        }
+    }
+
+    #[test]
+    fn pot_side_pots() {
+        let cases = vec![
+            (
+                "Everyone in for the same amount",
+                vec![50, 50, 50],
+                vec![false, false, false],
+                vec![pot(150, &[0, 1, 2])],
+            ),
+            (
+                "Three all ins for different amounts",
+                vec![25, 100, 60],
+                vec![false, false, false],
+                vec![pot(75, &[0, 1, 2]), pot(70, &[1, 2]), pot(40, &[1])],
+            ),
+            (
+                "Folded chips go in without making the folder eligible",
+                vec![30, 100, 50, 100],
+                vec![true, false, false, false],
+                vec![pot(180, &[1, 2, 3]), pot(100, &[1, 3])],
+            ),
+            (
+                "Folded chips above every live amount go in the last pot",
+                vec![80, 40, 40],
+                vec![true, false, false],
+                vec![pot(160, &[1, 2])],
+            ),
+            (
+                "Short all in with a folder in between",
+                vec![10, 20, 0, 40],
+                vec![false, true, false, false],
+                vec![pot(30, &[0, 3]), pot(40, &[3])],
+            ),
+            (
+                "Only folded chips go to whoever is left",
+                vec![10, 0],
+                vec![true, false],
+                vec![pot(10, &[1])],
+            ),
+            (
+                "Blinds folded to a player who put nothing in",
+                vec![1, 2, 0, 0],
+                vec![true, true, true, false],
+                vec![pot(3, &[3])],
+            ),
+            ("Nothing in the pot", vec![0, 0], vec![false, false], vec![]),
+        ];
+
+        for (name, contributions, folded, expected) in cases {
+            assert_eq!(side_pots(&contributions, &folded), expected, "Case: {name}");
+        }
+    }
+
+    #[test]
+    fn pot_side_pots_keep_every_chip() {
+        let mut rng = Rng::new(29);
+        for _ in 0..1000 {
+            let seats = 2 + rng.below(8);
+            let contributions: Vec<u32> = (0..seats).map(|_| rng.below(500) as u32).collect();
+            let mut folded: Vec<bool> = (0..seats).map(|_| rng.below(3) == 0).collect();
+            folded[rng.below(seats)] = false;
+
+            let pots = side_pots(&contributions, &folded);
+            let total: u32 = contributions.iter().sum();
+            assert_eq!(pots.iter().map(|pot| pot.amount).sum::<u32>(), total);
+            for pair in pots.windows(2) {
+                assert!(
+                    pair[1]
+                        .eligible
+                        .iter()
+                        .all(|seat| pair[0].eligible.contains(seat))
+                );
+            }
+            for pot in &pots {
+                assert!(!pot.eligible.is_empty());
+                assert!(pot.eligible.iter().all(|&seat| !folded[seat]));
+            }
+        }
+    }
+
+    #[test]
+    fn pot_award_pots() {
+        let aces = || {
+            hand(&[
+                (14, Suit::Spade),
+                (14, Suit::Heart),
+                (2, Suit::Club),
+                (7, Suit::Diamond),
+                (9, Suit::Club),
+            ])
+        };
+        let kings = || {
+            hand(&[
+                (13, Suit::Spade),
+                (13, Suit::Heart),
+                (2, Suit::Diamond),
+                (7, Suit::Club),
+                (9, Suit::Heart),
+            ])
+        };
+        let queens = || {
+            hand(&[
+                (12, Suit::Spade),
+                (12, Suit::Heart),
+                (2, Suit::Spade),
+                (7, Suit::Heart),
+                (9, Suit::Diamond),
+            ])
+        };
+
+        let cases = vec![
+            (
+                "Short stack wins the main pot, the next best the side pot",
+                vec![pot(75, &[0, 1, 2]), pot(70, &[1, 2]), pot(40, &[1])],
+                vec![aces(), queens(), kings()],
+                0,
+                vec![75, 40, 70],
+            ),
+            (
+                "Best hand takes every pot it is eligible for",
+                vec![pot(75, &[0, 1, 2]), pot(70, &[1, 2])],
+                vec![queens(), aces(), kings()],
+                0,
+                vec![0, 145, 0],
+            ),
+            (
+                "Split pot with an odd chip to the first seat left of the button",
+                vec![pot(101, &[0, 1, 2])],
+                vec![aces(), queens(), aces()],
+                1,
+                vec![50, 0, 51],
+            ),
+            (
+                "Three way split with two odd chips",
+                vec![pot(20, &[0, 1, 2])],
+                vec![kings(), kings(), kings()],
+                2,
+                vec![7, 7, 6],
+            ),
+            (
+                "Uncontested pot needs no hands",
+                vec![pot(30, &[2])],
+                vec![None, None, None],
+                0,
+                vec![0, 0, 30],
+            ),
+        ];
+
+        for (name, pots, hands, button, expected) in cases {
+            assert_eq!(
+                award_pots(&pots, &hands, button),
+                Ok(expected),
+                "Case: {name}"
+            );
+        }
+
+        // From what every seat put in to what every seat takes home.
+        let cases = vec![
+            (
+                "Three all ins tie and share every pot they reach",
+                vec![30, 60, 90, 90],
+                vec![false, false, false, false],
+                vec![aces(), aces(), aces(), kings()],
+                3,
+                vec![40, 85, 145, 0],
+            ),
+            (
+                "Odd chips in the main pot and the side pot",
+                vec![15, 40, 40, 13, 20],
+                vec![false, false, false, true, true],
+                vec![queens(), aces(), aces(), None, None],
+                0,
+                vec![0, 65, 63, 0, 0],
+            ),
+            (
+                "Folded blinds go to the only player left",
+                vec![10, 20, 0],
+                vec![true, true, false],
+                vec![None, None, None],
+                1,
+                vec![0, 0, 30],
+            ),
+        ];
+
+        for (name, contributions, folded, hands, button, expected) in cases {
+            let pots = side_pots(&contributions, &folded);
+            assert_eq!(
+                award_pots(&pots, &hands, button),
+                Ok(expected),
+                "Case: {name}"
+            );
+        }
+
+        assert_eq!(
+            award_pots(&[pot(40, &[0, 1])], &[None, None], 0),
+            Err("No eligible hand to award a pot to")
+        );
+        assert_eq!(
+            award_pots(&[pot(40, &[])], &[aces(), kings()], 0),
+            Err("No eligible hand to award a pot to")
+        );
+    }
+}
@@ -0,0 +1,451 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::str::FromStr;
+
+use crate::deck::SUITS;
+use crate::encoding::{self, invalid_data};
+use crate::equity::equity;
+use crate::simulation::Runner;
+use crate::{Card, Suit};
+
+/// Number of starting hands once suits only matter for suited or not: 13 pairs, 78 suited and
+/// 78 offsuit hands.
+pub const STARTING_HANDS: usize = 169;
+
+/// A Hold'em starting hand like `AA`, `AKs` or `72o`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StartingHand {
+    high: u8,
+    low: u8,
+    suited: bool,
+}
+
+impl StartingHand {
+    /// The starting hand two hole cards make.
+    pub fn of(cards: &[Card]) -> Result<Self, String> {
+        let [first, second] = cards else {
+            return Err(format!("Hands must have 2 cards, not {}", cards.len()));
+        };
+        encoding::mask(cards)?;
+
+        Ok(StartingHand {
+            high: first.rank.max(second.rank),
+            low: first.rank.min(second.rank),
+            suited: first.suit == second.suit,
+        })
+    }
+
+    /// Position in the usual 13 by 13 chart, from 0 to `STARTING_HANDS - 1`: row and column
+    /// go from aces down to deuces, pairs sit on the diagonal, suited hands above it and
+    /// offsuit hands below it.
+    pub fn index(&self) -> usize {
+        let (high, low) = (14 - self.high as usize, 14 - self.low as usize);
+        if self.suited {
+            high * 13 + low
+        } else {
+            low * 13 + high
+        }
+    }
+
+    /// The starting hand at `index`. Undoes `index`.
+    pub fn from_index(index: usize) -> Result<Self, String> {
+        if index >= STARTING_HANDS {
+            return Err(format!(
+                "{index} is past the last starting hand, {}",
+                STARTING_HANDS - 1
+            ));
+        }
+
+        let (row, column) = ((index / 13) as u8, (index % 13) as u8);
+        Ok(StartingHand {
+            high: 14 - row.min(column),
+            low: 14 - row.max(column),
+            suited: row < column,
+        })
+    }
+
+    /// Every starting hand, in `index` order.
+    pub fn all() -> Vec<Self> {
+        (0..STARTING_HANDS)
+            .map(|index| StartingHand::from_index(index).expect("index is in range"))
+            .collect()
+    }
+
+    pub fn is_pair(&self) -> bool {
+        self.high == self.low
+    }
+
+    pub fn is_suited(&self) -> bool {
+        self.suited
+    }
+
+    /// Every pair of hole cards that makes this hand: 6 for pairs, 4 suited, 12 offsuit.
+    pub fn combos(&self) -> Vec<[Card; 2]> {
+        let mut combos = Vec::new();
+        for (i, &first) in SUITS.iter().enumerate() {
+            for (j, &second) in SUITS.iter().enumerate() {
+                let wanted = if self.is_pair() {
+                    i < j
+                } else {
+                    (i == j) == self.suited
+                };
+                if wanted {
+                    combos.push([card(self.high, first), card(self.low, second)]);
+                }
+            }
+        }
+        combos
+    }
+}
+
+fn card(rank: u8, suit: Suit) -> Card {
+    Card { rank, suit }
+}
+
+impl fmt::Display for StartingHand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rank = |rank| card(rank, Suit::Club).to_string().remove(0);
+        write!(f, "{}{}", rank(self.high), rank(self.low))?;
+        match (self.is_pair(), self.suited) {
+            (true, _) => Ok(()),
+            (false, true) => write!(f, "s"),
+            (false, false) => write!(f, "o"),
+        }
+    }
+}
+
+impl FromStr for StartingHand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.trim().chars().collect();
+        let rank = |c: char| -> Result<u8, String> {
+            format!("{c}c")
+                .parse::<Card>()
+                .map(|card| card.rank)
+                .map_err(|_| format!("{s:?} has an unknown rank {c:?}"))
+        };
+
+        let (first, second, suited) = match chars.as_slice() {
+            [first, second] => (rank(*first)?, rank(*second)?, None),
+            [first, second, kind] => (
+                rank(*first)?,
+                rank(*second)?,
+                match kind.to_ascii_lowercase() {
+                    's' => Some(true),
+                    'o' => Some(false),
+                    _ => return Err(format!("{s:?} ends in {kind:?}, not s or o")),
+                },
+            ),
+            _ => return Err(format!("{s:?} is not a starting hand like AKs or QQ")),
+        };
+
+        match (first == second, suited) {
+            (true, None) => Ok(StartingHand {
+                high: first,
+                low: first,
+                suited: false,
+            }),
+            (true, Some(_)) => Err(format!("{s:?} is a pair, which is never suited or offsuit")),
+            (false, None) => Err(format!("{s:?} needs s or o for suited or offsuit")),
+            (false, Some(suited)) => Ok(StartingHand {
+                high: first.max(second),
+                low: first.min(second),
+                suited,
+            }),
+        }
+    }
+}
+
+/// Heads-up preflop equities between every two starting hands.
+///
+/// Only one triangle is kept: `hand` against `other` and `other` against `hand` add up to 1.
+/// Equities are stored in 16 bits, which is good to about 0.00001.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreflopTable {
+    samples: u64,
+    seed: u64,
+    equities: Vec<u16>,
+}
+
+const MAGIC: &[u8; 4] = b"PKPF";
+const VERSION: u8 = 1;
+const ENTRIES: usize = STARTING_HANDS * (STARTING_HANDS + 1) / 2;
+
+// Position of `hand` against `other` in the triangle, `hand` no later than `other`.
+fn entry(hand: usize, other: usize) -> usize {
+    hand * STARTING_HANDS - hand * (hand + 1) / 2 + other
+}
+
+impl PreflopTable {
+    /// Works out every matchup by dealing `samples` random boards for each way the suits of
+    /// the two hands can line up, spread over the threads of `runner`.
+    ///
+    /// Every matchup gets its own seed from the runner's seed, so the table comes out the same
+    /// whatever the number of threads.
+    pub fn generate(samples: u64, runner: &Runner) -> Result<Self, String> {
+        Self::generate_for(&StartingHand::all(), samples, runner)
+    }
+
+    // Fills in the matchups between `hands` only; the others stay at 0.
+    fn generate_for(hands: &[StartingHand], samples: u64, runner: &Runner) -> Result<Self, String> {
+        if samples == 0 {
+            return Err("Equity needs at least one board".to_string());
+        }
+
+        let seed = runner.seed();
+        let mut table = PreflopTable {
+            samples,
+            seed,
+            equities: vec![0; ENTRIES],
+        };
+        let mut jobs = Vec::new();
+        for (i, hand) in hands.iter().enumerate() {
+            for other in &hands[i..] {
+                let (a, b) = (hand.index(), other.index());
+                if a == b {
+                    // The same hand on both sides splits the equity evenly.
+                    table.set(a, a, 0.5);
+                } else {
+                    jobs.push((a.min(b), a.max(b)));
+                }
+            }
+        }
+
+        let report = runner.run(
+            jobs.len() as u64,
+            |range, _| {
+                range
+                    .map(|job| {
+                        let (a, b) = jobs[job as usize];
+                        matchup_equity(a, b, samples, seed).map(|equity| (a, b, equity))
+                    })
+                    .collect::<Vec<_>>()
+            },
+            |mut done, more| {
+                done.extend(more);
+                done
+            },
+        );
+
+        for result in report.output {
+            let (a, b, equity) = result?;
+            table.set(a, b, equity);
+        }
+        Ok(table)
+    }
+
+    fn set(&mut self, hand: usize, other: usize, equity: f64) {
+        self.equities[entry(hand, other)] = (equity * u16::MAX as f64).round() as u16;
+    }
+
+    /// Equity of `hand` against `other` before the flop.
+    pub fn equity(&self, hand: StartingHand, other: StartingHand) -> f64 {
+        let (a, b) = (hand.index(), other.index());
+        if a <= b {
+            self.equities[entry(a, b)] as f64 / u16::MAX as f64
+        } else {
+            1.0 - self.equities[entry(b, a)] as f64 / u16::MAX as f64
+        }
+    }
+
+    /// Boards dealt for each suit pattern of each matchup.
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Writes `PKPF`, a version byte, the samples and the seed as big-endian `u64`s, then the
+    /// triangle row by row as big-endian `u16`s.
+    pub fn write<W: Write>(&self, mut out: W) -> io::Result<()> {
+        out.write_all(MAGIC)?;
+        out.write_all(&[VERSION])?;
+        out.write_all(&self.samples.to_be_bytes())?;
+        out.write_all(&self.seed.to_be_bytes())?;
+        let mut bytes = Vec::with_capacity(2 * ENTRIES);
+        for equity in &self.equities {
+            bytes.extend(equity.to_be_bytes());
+        }
+        out.write_all(&bytes)
+    }
+
+    /// Reads a table written by `write`.
+    pub fn read<R: Read>(mut input: R) -> io::Result<Self> {
+        let mut header = [0; 5];
+        input.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(invalid_data("Not a preflop equity table".to_string()));
+        }
+        if header[4] != VERSION {
+            return Err(invalid_data(format!(
+                "Preflop table version {} is not supported",
+                header[4]
+            )));
+        }
+
+        let mut number = [0; 8];
+        input.read_exact(&mut number)?;
+        let samples = u64::from_be_bytes(number);
+        input.read_exact(&mut number)?;
+        let seed = u64::from_be_bytes(number);
+
+        let mut bytes = vec![0; 2 * ENTRIES];
+        input.read_exact(&mut bytes)?;
+        let equities = bytes
+            .chunks(2)
+            .map(|two| u16::from_be_bytes([two[0], two[1]]))
+            .collect();
+
+        Ok(PreflopTable {
+            samples,
+            seed,
+            equities,
+        })
+    }
+}
+
+// Equity of hand `a` against hand `b` (chart indexes).
+//
+// Any one combo of `a` stands for all of them. The combos of `b` that differ only by renaming
+// suits that leave the `a` combo alone do equally well, so each group is dealt once and
+// counted as many times as it has combos.
+fn matchup_equity(a: usize, b: usize, samples: u64, seed: u64) -> Result<f64, String> {
+    let hand = StartingHand::from_index(a)?.combos()[0];
+    let hand_bits = encoding::mask(&hand)?;
+
+    let renamings: Vec<[usize; 4]> = suit_permutations()
+        .into_iter()
+        .filter(|renaming| encoding::mask(&rename(&hand, renaming)) == Ok(hand_bits))
+        .collect();
+
+    let mut groups: Vec<(u64, [Card; 2], u64)> = Vec::new();
+    for other in StartingHand::from_index(b)?.combos() {
+        if encoding::mask(&other)? & hand_bits != 0 {
+            continue;
+        }
+        let key = renamings
+            .iter()
+            .map(|renaming| encoding::mask(&rename(&other, renaming)))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .min()
+            .expect("Leaving every suit alone is always a renaming");
+        match groups.iter_mut().find(|(found, _, _)| *found == key) {
+            Some((_, _, count)) => *count += 1,
+            None => groups.push((key, other, 1)),
+        }
+    }
+
+    let mut total = 0.0;
+    let mut combos = 0;
+    for (i, (_, other, count)) in groups.iter().enumerate() {
+        let matchup_seed = seed ^ ((a * STARTING_HANDS + b) as u64) << 8 ^ i as u64;
+        let result = equity(&[hand.to_vec(), other.to_vec()], &[], samples, matchup_seed)?;
+        total += result.equities[0] * *count as f64;
+        combos += count;
+    }
+    Ok(total / combos as f64)
+}
+
+fn suit_permutations() -> Vec<[usize; 4]> {
+    let mut permutations = Vec::new();
+    for a in 0..4 {
+        for b in (0..4).filter(|&b| b != a) {
+            for c in (0..4).filter(|&c| c != a && c != b) {
+                // Three different suits add up to at most 6, and the fourth is what is left.
+                permutations.push([a, b, c, 6 - a - b - c]);
+            }
+        }
+    }
+    permutations
+}
+
+fn rename(cards: &[Card; 2], renaming: &[usize; 4]) -> [Card; 2] {
+    cards.map(|card| {
+        let suit = SUITS
+            .iter()
+            .position(|suit| *suit == card.suit)
+            .expect("SUITS holds every suit");
+        Card {
+            rank: card.rank,
+            suit: SUITS[renaming[suit]],
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_cards;
+
+    fn hand(text: &str) -> StartingHand {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn preflop_starting_hands() {
+        let all = StartingHand::all();
+        assert_eq!(all.len(), STARTING_HANDS);
+        assert_eq!(all[0].to_string(), "AA");
+        assert_eq!(all[1].to_string(), "AKs");
+        assert_eq!(all[13].to_string(), "AKo");
+        assert_eq!(all[168].to_string(), "22");
+        for (index, starting) in all.iter().enumerate() {
+            assert_eq!(starting.index(), index);
+            assert_eq!(hand(&starting.to_string()), *starting);
+        }
+
+        let combos: usize = all.iter().map(|starting| starting.combos().len()).sum();
+        assert_eq!(combos, 1326);
+        assert_eq!(hand("QQ").combos().len(), 6);
+        assert_eq!(hand("T9s").combos().len(), 4);
+        assert_eq!(hand("72o").combos().len(), 12);
+
+        let cards = parse_cards("7h Ah").unwrap();
+        assert_eq!(StartingHand::of(&cards).unwrap(), hand("a7S"));
+        assert_eq!(
+            StartingHand::of(&parse_cards("7h 7d").unwrap()).unwrap(),
+            hand("77")
+        );
+
+        assert!("AK".parse::<StartingHand>().is_err());
+        assert!("AAs".parse::<StartingHand>().is_err());
+        assert!("AKx".parse::<StartingHand>().is_err());
+        assert!("A1s".parse::<StartingHand>().is_err());
+        assert!(StartingHand::from_index(STARTING_HANDS).is_err());
+    }
+
+    #[test]
+    fn preflop_table_generation_and_files() {
+        let hands = [hand("AA"), hand("KK"), hand("72o"), hand("AKs")];
+        let one = Runner::new(44).threads(1).chunk_size(1);
+        let table = PreflopTable::generate_for(&hands, 150, &one).unwrap();
+        let three = Runner::new(44).threads(3).chunk_size(2);
+        assert_eq!(
+            PreflopTable::generate_for(&hands, 150, &three).unwrap(),
+            table
+        );
+        assert!(PreflopTable::generate_for(&hands, 0, &three).is_err());
+
+        let aces_kings = table.equity(hand("AA"), hand("KK"));
+        assert!((0.76..0.88).contains(&aces_kings), "{aces_kings}");
+        assert!((table.equity(hand("KK"), hand("AA")) + aces_kings - 1.0).abs() < 1e-4);
+        assert!(table.equity(hand("AKs"), hand("72o")) > 0.6);
+        assert!((table.equity(hand("KK"), hand("KK")) - 0.5).abs() < 1e-4);
+
+        let mut bytes = Vec::new();
+        table.write(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 21 + 2 * ENTRIES);
+        let read = PreflopTable::read(bytes.as_slice()).unwrap();
+        assert_eq!(read, table);
+        assert_eq!((read.samples(), read.seed()), (150, 44));
+
+        bytes[4] = 9;
+        assert!(PreflopTable::read(bytes.as_slice()).is_err());
+        assert!(PreflopTable::read(&b"PKHS"[..]).is_err());
+        assert!(PreflopTable::read(&bytes[..100]).is_err());
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::{Card, PokerHand, PokerHandType};
+
+// Cards are written the way hand histories write them, e.g. `"Ah"`.
+impl Serialize for Card {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Card {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct CardVisitor;
+
+        impl Visitor<'_> for CardVisitor {
+            type Value = Card;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a card like \"Ah\" or \"Td\"")
+            }
+
+            // `FromStr` only accepts ranks 2 - 14, so no card with a bad rank gets through.
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Card, E> {
+                s.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(CardVisitor)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PokerHandRepr {
+    cards: Vec<Card>,
+    #[serde(default)]
+    hand_type: Option<PokerHandType>,
+}
+
+impl Serialize for PokerHand {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PokerHandRepr {
+            cards: self.cards().to_vec(),
+            hand_type: self.poker_hand_type.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Hands must have five different cards. They come back sorted, and when `hand_type` is given
+/// it has to match what the cards make.
+impl<'de> Deserialize<'de> for PokerHand {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = PokerHandRepr::deserialize(deserializer)?;
+
+        if repr.cards.len() != 5 {
+            return Err(de::Error::invalid_length(repr.cards.len(), &"five cards"));
+        }
+        for (i, card) in repr.cards.iter().enumerate() {
+            if repr.cards[i + 1..]
+                .iter()
+                .any(|other| other.is_same_card(card))
+            {
+                return Err(de::Error::custom(format!(
+                    "{card} shows up twice in the hand"
+                )));
+            }
+        }
+
+        let mut hand = PokerHand::new();
+        for card in repr.cards {
+            hand.add_card(card).map_err(de::Error::custom)?;
+        }
+        hand.sort_hand().map_err(de::Error::custom)?;
+        if let Some(hand_type) = repr.hand_type
+            && hand.poker_hand_type.as_ref() != Some(&hand_type)
+        {
+            return Err(de::Error::custom(format!(
+                "the cards make {:?}, not {hand_type:?}",
+                hand.poker_hand_type.expect("sort_hand sets the hand type")
+            )));
+        }
+
+        Ok(hand)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Suit, parse_cards};
+
+    fn hand(cards: &str) -> PokerHand {
+        let mut hand = PokerHand::new();
+        for card in parse_cards(cards).unwrap() {
+            hand.add_card(card).unwrap();
+        }
+        hand.sort_hand().unwrap();
+        hand
+    }
+
+    #[test]
+    fn serde_card() {
+        let card = Card::new(14, Suit::Heart).unwrap();
+        assert_eq!(serde_json::to_string(&card).unwrap(), "\"Ah\"");
+
+        let cards: Vec<Card> = serde_json::from_str(r#"["Td", "2c", "Ks"]"#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&cards).unwrap(),
+            r#"["Td","2c","Ks"]"#
+        );
+
+        for bad in [r#""1h""#, r#""15h""#, r#""Ax""#, r#""""#, "14"] {
+            assert!(serde_json::from_str::<Card>(bad).is_err(), "{bad}");
+        }
+
+        assert_eq!(serde_json::to_string(&Suit::Spade).unwrap(), "\"Spade\"");
+        assert_eq!(
+            serde_json::from_str::<PokerHandType>("\"FullHouse\"").unwrap(),
+            PokerHandType::FullHouse
+        );
+    }
+
+    #[test]
+    fn serde_poker_hand() {
+        let full_house = hand("Kd Ks 3h Kc 3s");
+        let json = serde_json::to_string(&full_house).unwrap();
+        assert_eq!(
+            json,
+            r#"{"cards":["Kd","Ks","Kc","3h","3s"],"hand_type":"FullHouse"}"#
+        );
+
+        let back: PokerHand = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, full_house);
+        assert_eq!(back.poker_hand_type, Some(PokerHandType::FullHouse));
+
+        // Without a hand type the cards are evaluated, and the hand can be compared right away.
+        let flush: PokerHand =
+            serde_json::from_str(r#"{"cards":["2h","9h","4h","Jh","6h"]}"#).unwrap();
+        assert_eq!(flush.poker_hand_type, Some(PokerHandType::Flush));
+        assert!(flush < full_house);
+    }
+
+    #[test]
+    fn serde_poker_hand_validation() {
+        let cases = [
+            (
+                r#"{"cards":["2h","9h","4h","Jh"]}"#,
+                "invalid length 4, expected five cards",
+            ),
+            (
+                r#"{"cards":["2h","9h","4h","Jh","6h","7h"]}"#,
+                "invalid length 6, expected five cards",
+            ),
+            (
+                r#"{"cards":["2h","9h","4h","Jh","2h"]}"#,
+                "2h shows up twice in the hand",
+            ),
+            (
+                r#"{"cards":["2h","9h","4h","Jh","0h"]}"#,
+                "\"0h\" has an unknown rank",
+            ),
+            (
+                r#"{"cards":["2h","9h","4h","Jh","6h"],"hand_type":"RoyalFlush"}"#,
+                "the cards make Flush, not RoyalFlush",
+            ),
+        ];
+
+        for (json, expected) in cases {
+            let error = serde_json::from_str::<PokerHand>(json)
+                .unwrap_err()
+                .to_string();
+            assert!(error.starts_with(expected), "{json}: {error}");
+        }
+    }
+}
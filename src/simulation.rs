@@ -0,0 +1,268 @@
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::deck::Rng;
+
+/// How far a run has got, handed to the `on_progress` callback after every chunk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    pub done: u64,
+    pub total: u64,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Share of the items done, from 0 to 1.
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.done as f64 / self.total as f64
+        }
+    }
+
+    /// Items done per second so far.
+    pub fn per_second(&self) -> f64 {
+        per_second(self.done, self.elapsed)
+    }
+}
+
+/// What a run produced and how long it took.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report<T> {
+    pub output: T,
+    pub items: u64,
+    pub threads: usize,
+    pub elapsed: Duration,
+}
+
+impl<T> Report<T> {
+    /// Items, e.g. hands or boards, per second.
+    pub fn per_second(&self) -> f64 {
+        per_second(self.items, self.elapsed)
+    }
+}
+
+fn per_second(items: u64, elapsed: Duration) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds == 0.0 {
+        0.0
+    } else {
+        items as f64 / seconds
+    }
+}
+
+type ProgressFn<'a> = Box<dyn Fn(&Progress) + Sync + 'a>;
+
+/// Splits a run of numbered items into chunks and works through them on several threads.
+///
+/// Every chunk gets its own random number generator, seeded from the run's seed and the chunk
+/// number, and the chunk results are merged in chunk order. So a run comes out the same
+/// however many threads it uses, as long as merging does not care how results are grouped.
+pub struct Runner<'a> {
+    seed: u64,
+    threads: usize,
+    chunk_size: u64,
+    progress: Option<ProgressFn<'a>>,
+}
+
+impl<'a> Runner<'a> {
+    /// A runner on every available core, in chunks of 1,000 items.
+    pub fn new(seed: u64) -> Self {
+        Runner {
+            seed,
+            threads: std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+            chunk_size: 1_000,
+            progress: None,
+        }
+    }
+
+    /// Runs on `threads` threads, at least one.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Hands out `chunk_size` items at a time, at least one. The chunk size decides how the
+    /// random numbers are split up, so changing it changes sampled results.
+    pub fn chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Calls `progress` after every chunk, from whichever thread finished it.
+    pub fn on_progress(mut self, progress: impl Fn(&Progress) + Sync + 'a) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Runs `work` over the items `0..items`, a chunk at a time, and folds the chunk results
+    /// together with `merge`, starting from `T::default()`.
+    pub fn run<T, W, M>(&self, items: u64, work: W, mut merge: M) -> Report<T>
+    where
+        T: Default + Send,
+        W: Fn(Range<u64>, &mut Rng) -> T + Sync,
+        M: FnMut(T, T) -> T,
+    {
+        let start = Instant::now();
+        let chunks = items.div_ceil(self.chunk_size);
+        let next = AtomicU64::new(0);
+        let done = AtomicU64::new(0);
+        let threads = self.threads.min(chunks.max(1) as usize);
+
+        let mut results: Vec<(u64, T)> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = Vec::new();
+                        loop {
+                            let chunk = next.fetch_add(1, Ordering::Relaxed);
+                            if chunk >= chunks {
+                                return results;
+                            }
+                            let range =
+                                chunk * self.chunk_size..((chunk + 1) * self.chunk_size).min(items);
+                            let len = range.end - range.start;
+                            // A hashed seed per chunk, so chunks do not share a stream.
+                            let mut rng =
+                                Rng::new(Rng::new(self.seed.wrapping_add(chunk)).next_u64());
+                            results.push((chunk, work(range, &mut rng)));
+
+                            let done = done.fetch_add(len, Ordering::Relaxed) + len;
+                            if let Some(progress) = &self.progress {
+                                progress(&Progress {
+                                    done,
+                                    total: items,
+                                    elapsed: start.elapsed(),
+                                });
+                            }
+                        }
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("Simulation threads do not panic"))
+                .collect()
+        });
+
+        results.sort_by_key(|(chunk, _)| *chunk);
+        let output = results
+            .into_iter()
+            .fold(T::default(), |merged, (_, result)| merge(merged, result));
+        Report {
+            output,
+            items,
+            threads,
+            elapsed: start.elapsed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::deck::Deck;
+    use crate::{PokerHand, PokerHandType, index};
+
+    // How often each hand type comes up.
+    fn count_types(counts: Vec<u64>, other: Vec<u64>) -> Vec<u64> {
+        if counts.is_empty() {
+            return other;
+        }
+        counts.iter().zip(&other).map(|(a, b)| a + b).collect()
+    }
+
+    fn type_slot(hand: &PokerHand) -> usize {
+        match hand.poker_hand_type().expect("best_of sorts the hand") {
+            PokerHandType::HighCard => 0,
+            PokerHandType::OnePair => 1,
+            PokerHandType::TwoPair => 2,
+            PokerHandType::ThreeOfAKind => 3,
+            PokerHandType::Straight => 4,
+            PokerHandType::Flush => 5,
+            PokerHandType::FullHouse => 6,
+            PokerHandType::FourOfAKind => 7,
+            PokerHandType::StraightFlush => 8,
+            PokerHandType::RoyalFlush => 9,
+        }
+    }
+
+    #[test]
+    fn simulation_is_the_same_on_any_number_of_threads() {
+        let random_hands = |range: Range<u64>, rng: &mut Rng| {
+            let mut counts = vec![0; 10];
+            for _ in range {
+                let mut deck = Deck::new();
+                deck.shuffle(rng);
+                let cards: Vec<_> = (0..5).map(|_| deck.deal().unwrap()).collect();
+                counts[type_slot(&PokerHand::best_of(&cards).unwrap())] += 1;
+            }
+            counts
+        };
+
+        let one = Runner::new(45)
+            .threads(1)
+            .chunk_size(64)
+            .run(1500, random_hands, count_types);
+        let four = Runner::new(45)
+            .threads(4)
+            .chunk_size(64)
+            .run(1500, random_hands, count_types);
+        assert_eq!(one.output, four.output);
+        assert_eq!((one.threads, four.threads), (1, 4));
+        assert_eq!(one.output.iter().sum::<u64>(), 1500);
+        // About 42% of hands are one pair.
+        assert!((540..720).contains(&one.output[1]), "{:?}", one.output);
+
+        let other_seed = Runner::new(46)
+            .chunk_size(64)
+            .run(1500, random_hands, count_types);
+        assert_ne!(other_seed.output, one.output);
+    }
+
+    #[test]
+    fn simulation_progress_and_exhaustive_runs() {
+        let updates = Mutex::new(Vec::new());
+        let report = Runner::new(0)
+            .threads(3)
+            .chunk_size(500)
+            .on_progress(|progress| updates.lock().unwrap().push(progress.done))
+            .run(
+                2_000,
+                |range, _| {
+                    // Exhaustive over a slice of the five card hands, by index.
+                    let mut counts = vec![0; 10];
+                    for i in range {
+                        let hand = index::hand_at(i as u32 * 1_000, 5).unwrap();
+                        counts[type_slot(&PokerHand::best_of(&hand).unwrap())] += 1;
+                    }
+                    counts
+                },
+                count_types,
+            );
+        assert_eq!(report.items, 2_000);
+        assert_eq!(report.output.iter().sum::<u64>(), 2_000);
+        assert!(report.per_second() > 0.0);
+
+        let mut updates = updates.into_inner().unwrap();
+        updates.sort();
+        assert_eq!(updates, vec![500, 1_000, 1_500, 2_000]);
+
+        let empty = Runner::new(0).run(0, |_, _| 1u64, |a, b| a + b);
+        assert_eq!((empty.output, empty.threads), (0, 1));
+        let progress = Progress {
+            done: 1,
+            total: 4,
+            elapsed: Duration::from_millis(500),
+        };
+        assert_eq!((progress.fraction(), progress.per_second()), (0.25, 2.0));
+    }
+}
@@ -0,0 +1,533 @@
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// How one key compared two records, before its direction is applied.
+enum Compared {
+    Values(Ordering),
+    // Exactly one of the two records has no value for the key.
+    FirstMissing,
+    SecondMissing,
+}
+
+type CompareFn<T> = Box<dyn Fn(&T, &T) -> Compared>;
+
+struct Key<T> {
+    compare: CompareFn<T>,
+    descending: bool,
+    nulls_first: bool,
+}
+
+/// A multi-key sort order for records, built up one key at a time instead of chaining `then`
+/// by hand, e.g. `SortSpec::by(|e: &Employee| e.years).desc().then_by_ref(|e| &e.name)`.
+///
+/// `by` takes keys the closure hands back by value, `by_ref` keys it borrows from the record.
+/// The `option` variants take `Option` keys, whose `None`s go last unless `nulls_first` says
+/// otherwise, whichever the direction.
+pub struct SortSpec<T> {
+    keys: Vec<Key<T>>,
+}
+
+impl<T: 'static> Default for SortSpec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: 'static> SortSpec<T> {
+    /// No keys: every record compares equal, so a stable sort keeps them as they are.
+    pub fn new() -> Self {
+        SortSpec { keys: Vec::new() }
+    }
+
+    pub fn by<K: Ord>(key: impl Fn(&T) -> K + 'static) -> Self {
+        Self::new().then_by(key)
+    }
+
+    pub fn by_ref<K: Ord + ?Sized>(key: impl Fn(&T) -> &K + 'static) -> Self {
+        Self::new().then_by_ref(key)
+    }
+
+    pub fn by_option<K: Ord>(key: impl Fn(&T) -> Option<K> + 'static) -> Self {
+        Self::new().then_by_option(key)
+    }
+
+    pub fn by_option_ref<K: Ord + ?Sized>(key: impl Fn(&T) -> Option<&K> + 'static) -> Self {
+        Self::new().then_by_option_ref(key)
+    }
+
+    /// Breaks ties with another key, ascending until `desc` is called.
+    pub fn then_by<K: Ord>(self, key: impl Fn(&T) -> K + 'static) -> Self {
+        self.push(move |a, b| Compared::Values(key(a).cmp(&key(b))))
+    }
+
+    pub fn then_by_ref<K: Ord + ?Sized>(self, key: impl Fn(&T) -> &K + 'static) -> Self {
+        self.push(move |a, b| Compared::Values(key(a).cmp(key(b))))
+    }
+
+    pub fn then_by_option<K: Ord>(self, key: impl Fn(&T) -> Option<K> + 'static) -> Self {
+        self.push(move |a, b| compare_options(key(a).as_ref(), key(b).as_ref()))
+    }
+
+    pub fn then_by_option_ref<K: Ord + ?Sized>(
+        self,
+        key: impl Fn(&T) -> Option<&K> + 'static,
+    ) -> Self {
+        self.push(move |a, b| compare_options(key(a), key(b)))
+    }
+
+    /// Adds the keys of `other` after these, to break the ties these leave.
+    pub fn then(mut self, other: SortSpec<T>) -> Self {
+        self.keys.extend(other.keys);
+        self
+    }
+
+    fn push(mut self, compare: impl Fn(&T, &T) -> Compared + 'static) -> Self {
+        self.keys.push(Key {
+            compare: Box::new(compare),
+            descending: false,
+            nulls_first: false,
+        });
+        self
+    }
+
+    // The key added last, which `asc`, `desc` and the nulls settings apply to.
+    fn last_key(&mut self) -> Option<&mut Key<T>> {
+        self.keys.last_mut()
+    }
+
+    /// Sorts the last key smallest first. This is the default.
+    pub fn asc(mut self) -> Self {
+        if let Some(key) = self.last_key() {
+            key.descending = false;
+        }
+        self
+    }
+
+    /// Sorts the last key largest first.
+    pub fn desc(mut self) -> Self {
+        if let Some(key) = self.last_key() {
+            key.descending = true;
+        }
+        self
+    }
+
+    /// Puts records without a value for the last key first. Only `Option` keys have those.
+    pub fn nulls_first(mut self) -> Self {
+        if let Some(key) = self.last_key() {
+            key.nulls_first = true;
+        }
+        self
+    }
+
+    /// Puts records without a value for the last key last. This is the default.
+    pub fn nulls_last(mut self) -> Self {
+        if let Some(key) = self.last_key() {
+            key.nulls_first = false;
+        }
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Compares two records key by key, until one of the keys tells them apart.
+    pub fn compare(&self, a: &T, b: &T) -> Ordering {
+        for key in &self.keys {
+            let ordering = match (key.compare)(a, b) {
+                Compared::Values(ordering) if key.descending => ordering.reverse(),
+                Compared::Values(ordering) => ordering,
+                Compared::FirstMissing if key.nulls_first => Ordering::Less,
+                Compared::FirstMissing => Ordering::Greater,
+                Compared::SecondMissing if key.nulls_first => Ordering::Greater,
+                Compared::SecondMissing => Ordering::Less,
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    }
+
+    /// `compare` as a closure, for `sort_by`, `max_by` and the like.
+    pub fn comparator(&self) -> impl Fn(&T, &T) -> Ordering + '_ {
+        |a, b| self.compare(a, b)
+    }
+
+    /// Stable sort of `records` in this order.
+    pub fn sort(&self, records: &mut [T]) {
+        records.sort_by(self.comparator());
+    }
+}
+
+fn compare_options<K: Ord + ?Sized>(a: Option<&K>, b: Option<&K>) -> Compared {
+    match (a, b) {
+        (Some(a), Some(b)) => Compared::Values(a.cmp(b)),
+        (None, None) => Compared::Values(Ordering::Equal),
+        (None, Some(_)) => Compared::FirstMissing,
+        (Some(_), None) => Compared::SecondMissing,
+    }
+}
+
+/// A value a record hands out for sorting on one of its fields by name.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FieldValue {
+    Bool(bool),
+    Int(i128),
+    Char(char),
+    Text(String),
+}
+
+/// Converts a field to the `FieldValue` it sorts by, `None` for a missing value.
+pub trait ToFieldValue {
+    fn to_field_value(&self) -> Option<FieldValue>;
+}
+
+macro_rules! int_field_values {
+    ($($int:ty),*) => {
+        $(impl ToFieldValue for $int {
+            fn to_field_value(&self) -> Option<FieldValue> {
+                Some(FieldValue::Int(*self as i128))
+            }
+        })*
+    };
+}
+
+int_field_values!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, usize);
+
+impl ToFieldValue for bool {
+    fn to_field_value(&self) -> Option<FieldValue> {
+        Some(FieldValue::Bool(*self))
+    }
+}
+
+impl ToFieldValue for char {
+    fn to_field_value(&self) -> Option<FieldValue> {
+        Some(FieldValue::Char(*self))
+    }
+}
+
+impl ToFieldValue for str {
+    fn to_field_value(&self) -> Option<FieldValue> {
+        Some(FieldValue::Text(self.to_string()))
+    }
+}
+
+impl ToFieldValue for String {
+    fn to_field_value(&self) -> Option<FieldValue> {
+        self.as_str().to_field_value()
+    }
+}
+
+impl<V: ToFieldValue + ?Sized> ToFieldValue for &V {
+    fn to_field_value(&self) -> Option<FieldValue> {
+        (**self).to_field_value()
+    }
+}
+
+impl<V: ToFieldValue> ToFieldValue for Option<V> {
+    fn to_field_value(&self) -> Option<FieldValue> {
+        self.as_ref()?.to_field_value()
+    }
+}
+
+/// Records whose fields can be looked up by name, for sort orders parsed from text. Write it
+/// by hand or with `impl_fields!`.
+pub trait Fields {
+    /// Every field `field` knows.
+    const FIELDS: &'static [&'static str];
+
+    /// The value of the field called `name`, `None` when the record has none.
+    fn field(&self, name: &str) -> Option<FieldValue>;
+}
+
+/// Implements `Fields` for a struct from a list of its fields, each of a type that implements
+/// `ToFieldValue`: `impl_fields!(Employee { name, years_of_service });`
+#[macro_export]
+macro_rules! impl_fields {
+    ($record:ty { $($field:ident),* $(,)? }) => {
+        impl $crate::sort::Fields for $record {
+            const FIELDS: &'static [&'static str] = &[$(stringify!($field)),*];
+
+            fn field(&self, name: &str) -> Option<$crate::sort::FieldValue> {
+                match name {
+                    $(stringify!($field) => {
+                        $crate::sort::ToFieldValue::to_field_value(&self.$field)
+                    })*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+/// Parses sort orders like `"years_of_service desc, name asc"`: fields split by commas, each
+/// followed by an optional `asc` or `desc` and an optional `nulls first` or `nulls last`.
+/// Keywords can be in any case, field names must match `Fields::FIELDS`.
+impl<T: Fields + 'static> FromStr for SortSpec<T> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Err("The sort order is empty".to_string());
+        }
+
+        let mut spec = SortSpec::new();
+        for (i, term) in s.split(',').enumerate() {
+            let words: Vec<&str> = term.split_whitespace().collect();
+            let Some((&field, mut rest)) = words.split_first() else {
+                return Err(format!("Sort key {} is empty", i + 1));
+            };
+            if !T::FIELDS.contains(&field) {
+                return Err(format!(
+                    "Unknown field {field:?}, expected one of: {}",
+                    T::FIELDS.join(", ")
+                ));
+            }
+
+            let name = field.to_string();
+            spec = spec.then_by_option(move |record: &T| record.field(&name));
+
+            let is = |word: &str, keyword: &str| word.eq_ignore_ascii_case(keyword);
+            if let Some((&direction, more)) = rest.split_first()
+                && !is(direction, "nulls")
+            {
+                spec = if is(direction, "asc") {
+                    spec.asc()
+                } else if is(direction, "desc") {
+                    spec.desc()
+                } else {
+                    return Err(format!(
+                        "Unknown direction {direction:?} for {field}, expected asc or desc"
+                    ));
+                };
+                rest = more;
+            }
+
+            match rest {
+                [] => {}
+                [nulls, first] if is(nulls, "nulls") && is(first, "first") => {
+                    spec = spec.nulls_first();
+                }
+                [nulls, last] if is(nulls, "nulls") && is(last, "last") => {
+                    spec = spec.nulls_last();
+                }
+                [nulls, ..] if is(nulls, "nulls") => {
+                    return Err(format!(
+                        "Expected nulls first or nulls last after {field}, not {:?}",
+                        rest.join(" ")
+                    ));
+                }
+                _ => {
+                    return Err(format!(
+                        "Unexpected {:?} after the sort key {field:?}",
+                        rest.join(" ")
+                    ));
+                }
+            }
+        }
+
+        Ok(spec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Employee {
+        name: String,
+        years_of_service: u32,
+        manager: Option<String>,
+    }
+
+    fn staff() -> Vec<Employee> {
+        [
+            ("Marcus", 2, Some("Jovanna")),
+            ("Jovanna", 5, None),
+            ("Carmen", 2, Some("Jovanna")),
+            ("Dillon", 0, Some("Carmen")),
+            ("Christy", 2, None),
+            ("Jerry", 1, Some("Carmen")),
+        ]
+        .into_iter()
+        .map(|(name, years_of_service, manager)| Employee {
+            name: name.to_string(),
+            years_of_service,
+            manager: manager.map(str::to_string),
+        })
+        .collect()
+    }
+
+    crate::impl_fields!(Employee {
+        name,
+        years_of_service,
+        manager,
+    });
+
+    fn sorted_names(spec: &SortSpec<Employee>) -> Vec<String> {
+        let mut staff = staff();
+        spec.sort(&mut staff);
+        staff.into_iter().map(|employee| employee.name).collect()
+    }
+
+    #[test]
+    fn sort_spec_keys_and_directions() {
+        // The hand-written comparator of the `Employee` examples.
+        let mut by_hand = staff();
+        by_hand.sort_by(|a, b| {
+            a.years_of_service
+                .cmp(&b.years_of_service)
+                .then(a.name.cmp(&b.name))
+        });
+        let spec = SortSpec::by(|e: &Employee| e.years_of_service).then_by_ref(|e| &e.name);
+        let mut with_spec = staff();
+        with_spec.sort_by(spec.comparator());
+        assert_eq!(with_spec, by_hand);
+        assert_eq!(spec.len(), 2);
+
+        assert_eq!(
+            sorted_names(
+                &SortSpec::by(|e: &Employee| e.years_of_service)
+                    .desc()
+                    .then_by_ref(|e| e.name.as_str())
+            ),
+            ["Jovanna", "Carmen", "Christy", "Marcus", "Jerry", "Dillon"]
+        );
+        assert_eq!(
+            sorted_names(&SortSpec::by_ref(|e: &Employee| &e.name).desc().asc()),
+            ["Carmen", "Christy", "Dillon", "Jerry", "Jovanna", "Marcus"]
+        );
+
+        // No keys keeps the order, and so do ties, as the sort is stable.
+        let names: Vec<_> = staff().into_iter().map(|e| e.name).collect();
+        assert_eq!(sorted_names(&SortSpec::new()), names);
+        assert!(SortSpec::<Employee>::new().desc().is_empty());
+    }
+
+    #[test]
+    fn sort_spec_nulls_and_composition() {
+        let by_manager = || SortSpec::by_option_ref(|e: &Employee| e.manager.as_deref());
+        assert_eq!(
+            sorted_names(&by_manager().then_by_ref(|e| &e.name)),
+            ["Dillon", "Jerry", "Carmen", "Marcus", "Christy", "Jovanna"]
+        );
+        assert_eq!(
+            sorted_names(&by_manager().nulls_first().then_by_ref(|e| &e.name)),
+            ["Christy", "Jovanna", "Dillon", "Jerry", "Carmen", "Marcus"]
+        );
+        // The direction does not move the nulls.
+        assert_eq!(
+            sorted_names(&by_manager().desc().then_by_ref(|e| &e.name)),
+            ["Carmen", "Marcus", "Dillon", "Jerry", "Christy", "Jovanna"]
+        );
+        assert_eq!(
+            sorted_names(
+                &SortSpec::by_option(|e: &Employee| {
+                    (e.years_of_service > 1).then_some(e.years_of_service)
+                })
+                .nulls_first()
+                .desc()
+                .then_by_ref(|e| &e.name)
+            ),
+            ["Dillon", "Jerry", "Jovanna", "Carmen", "Christy", "Marcus"]
+        );
+
+        let seniority = SortSpec::by(|e: &Employee| e.years_of_service).desc();
+        let names = SortSpec::by_ref(|e: &Employee| &e.name);
+        let composed = seniority.then(names);
+        assert_eq!(composed.len(), 2);
+        assert_eq!(
+            sorted_names(&composed),
+            ["Jovanna", "Carmen", "Christy", "Marcus", "Jerry", "Dillon"]
+        );
+
+        let staff = staff();
+        // First in the sorted order, so the longest serving.
+        let most_senior = staff.iter().min_by(|a, b| composed.compare(a, b));
+        assert_eq!(most_senior.map(|e| e.name.as_str()), Some("Jovanna"));
+    }
+
+    fn parse(order: &str) -> Result<SortSpec<Employee>, String> {
+        order.parse()
+    }
+
+    #[test]
+    fn sort_order_parsing() {
+        assert_eq!(
+            sorted_names(&parse("years_of_service desc, name asc").unwrap()),
+            ["Jovanna", "Carmen", "Christy", "Marcus", "Jerry", "Dillon"]
+        );
+        assert_eq!(
+            sorted_names(&parse(" years_of_service ,name").unwrap()),
+            ["Dillon", "Jerry", "Carmen", "Christy", "Marcus", "Jovanna"]
+        );
+        assert_eq!(
+            sorted_names(&parse("manager DESC NULLS FIRST, name").unwrap()),
+            ["Christy", "Jovanna", "Carmen", "Marcus", "Dillon", "Jerry"]
+        );
+        assert_eq!(
+            sorted_names(&parse("manager nulls last,name desc").unwrap()),
+            ["Jerry", "Dillon", "Marcus", "Carmen", "Jovanna", "Christy"]
+        );
+        assert_eq!(parse("name, manager").unwrap().len(), 2);
+
+        let error = |order: &str| parse(order).err().unwrap();
+        assert_eq!(
+            error("salary desc"),
+            "Unknown field \"salary\", expected one of: name, years_of_service, manager"
+        );
+        assert_eq!(
+            error("years_of_service downward"),
+            "Unknown direction \"downward\" for years_of_service, expected asc or desc"
+        );
+        assert_eq!(
+            error("manager asc nulls middle"),
+            "Expected nulls first or nulls last after manager, not \"nulls middle\""
+        );
+        assert_eq!(
+            error("name asc please"),
+            "Unexpected \"please\" after the sort key \"name\""
+        );
+        assert_eq!(error("name,, manager"), "Sort key 2 is empty");
+        assert_eq!(error("  "), "The sort order is empty");
+    }
+
+    // A record with its field access written by hand.
+    struct Card {
+        rank: u8,
+        suit: char,
+    }
+
+    impl Fields for Card {
+        const FIELDS: &'static [&'static str] = &["rank", "suit"];
+
+        fn field(&self, name: &str) -> Option<FieldValue> {
+            match name {
+                "rank" => self.rank.to_field_value(),
+                "suit" => self.suit.to_field_value(),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn sort_order_over_hand_written_fields() {
+        let mut cards: Vec<Card> = [(10, 's'), (14, 'c'), (10, 'd'), (2, 'h')]
+            .into_iter()
+            .map(|(rank, suit)| Card { rank, suit })
+            .collect();
+        "rank desc, suit"
+            .parse::<SortSpec<Card>>()
+            .unwrap()
+            .sort(&mut cards);
+        let sorted: Vec<_> = cards.iter().map(|card| (card.rank, card.suit)).collect();
+        assert_eq!(sorted, [(14, 'c'), (10, 'd'), (10, 's'), (2, 'h')]);
+        assert!("colour".parse::<SortSpec<Card>>().is_err());
+    }
+}
@@ -0,0 +1,616 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::{Card, PokerHand, combinations};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StudGame {
+    SevenCardStud,
+    // A-5 lowball: aces are low, straights and flushes do not count against you.
+    Razz,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StudCard {
+    pub card: Card,
+    pub face_up: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct StudHand {
+    cards: Vec<StudCard>,
+}
+
+impl StudHand {
+    pub fn new() -> Self {
+        StudHand { cards: Vec::new() }
+    }
+
+    /// Deals the next card to the hand. Third street is two cards down and one up, fourth
+    /// through sixth street are up and seventh street is down.
+    pub fn add_card(&mut self, card: Card) -> Result<(), &'static str> {
+        let face_up = !matches!(self.cards.len(), 0 | 1 | 6);
+        self.add_card_facing(card, face_up)
+    }
+
+    /// Deals the next card with an explicit facing, e.g. a community card when the deck runs out.
+    pub fn add_card_facing(&mut self, card: Card, face_up: bool) -> Result<(), &'static str> {
+        // Our card hand limit is 7.
+        if self.cards.len() == 7 {
+            return Err("Max stud hand limit is 7");
+        }
+
+        self.cards.push(StudCard { card, face_up });
+        Ok(())
+    }
+
+    pub fn cards(&self) -> &[StudCard] {
+        &self.cards
+    }
+
+    pub fn up_cards(&self) -> Vec<Card> {
+        self.cards
+            .iter()
+            .filter(|stud_card| stud_card.face_up)
+            .map(|stud_card| stud_card.card)
+            .collect()
+    }
+
+    fn all_cards(&self) -> Vec<Card> {
+        self.cards.iter().map(|stud_card| stud_card.card).collect()
+    }
+
+    /// Best five card high hand out of every card in the hand.
+    pub fn best_hand(&self) -> Result<PokerHand, &'static str> {
+        PokerHand::best_of(&self.all_cards())
+    }
+
+    /// Best five card A-5 low out of every card in the hand.
+    pub fn razz_low(&self) -> Result<RazzLow, &'static str> {
+        RazzLow::best_of(&self.all_cards())
+    }
+}
+
+/// An A-5 low hand. The better (lower) hand compares as `Ordering::Greater`, the same way a
+/// better `PokerHand` does, so the winner is always the `max`.
+#[derive(Debug)]
+pub struct RazzLow {
+    cards: Vec<Card>,
+    key: RankKey,
+}
+
+impl RazzLow {
+    pub fn best_of(cards: &[Card]) -> Result<RazzLow, &'static str> {
+        if cards.len() < 5 {
+            return Err("Must have at least 5 Cards to pick the best low");
+        }
+
+        combinations(cards.len(), 5)
+            .into_iter()
+            .map(|indexes| {
+                let cards: Vec<Card> = indexes.iter().map(|&i| cards[i]).collect();
+                let key = low_key(&cards);
+                RazzLow { cards, key }
+            })
+            .max()
+            .ok_or("Unable to pick the best low")
+    }
+
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    /// Ranks from the highest card down with the ace counted as 1, e.g. `[5, 4, 3, 2, 1]` for the wheel.
+    pub fn ranks(&self) -> Vec<u8> {
+        self.key
+            .1
+            .iter()
+            .flat_map(|&(count, rank)| std::iter::repeat_n(rank, count))
+            .collect()
+    }
+}
+
+/// Equal when neither low is better, like `cmp`, whatever the suits or the order of the cards.
+impl PartialEq for RazzLow {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for RazzLow {}
+
+impl PartialOrd for RazzLow {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RazzLow {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key).reverse()
+    }
+}
+
+/// Returns the seat that has to post the bring-in on third street, `None` if no one has an upcard.
+///
+/// In stud the lowest upcard brings in (aces are high) and the lowest suit breaks ties. In Razz
+/// it is the other way around: the highest upcard (kings, aces are low) and the highest suit.
+pub fn bring_in(hands: &[StudHand], game: StudGame) -> Option<usize> {
+    let door_cards = hands
+        .iter()
+        .enumerate()
+        .filter_map(|(seat, hand)| hand.up_cards().first().map(|card| (seat, *card)));
+
+    match game {
+        StudGame::SevenCardStud => door_cards
+            .min_by_key(|(_, card)| (card.rank, card.suit))
+            .map(|(seat, _)| seat),
+        StudGame::Razz => door_cards
+            .max_by_key(|(_, card)| (low_rank(card.rank), card.suit))
+            .map(|(seat, _)| seat),
+    }
+}
+
+/// Returns the seat that acts first on fourth street and later, `None` if no one has an upcard.
+///
+/// In stud the highest showing hand acts first, in Razz the lowest. Only pairs, trips and quads
+/// count on board; straights and flushes are never shown with four upcards. `hands` must be in
+/// seat order starting left of the dealer, so on a tie the first seat in the slice acts.
+pub fn first_to_act(hands: &[StudHand], game: StudGame) -> Option<usize> {
+    let mut first: Option<(usize, RankKey)> = None;
+
+    for (seat, hand) in hands.iter().enumerate() {
+        let up_cards = hand.up_cards();
+        if up_cards.is_empty() {
+            continue;
+        }
+
+        let key = match game {
+            StudGame::SevenCardStud => high_key(&up_cards),
+            StudGame::Razz => low_key(&up_cards),
+        };
+
+        let better = match &first {
+            None => true,
+            Some((_, best)) => match game {
+                StudGame::SevenCardStud => key > *best,
+                StudGame::Razz => key < *best,
+            },
+        };
+        if better {
+            first = Some((seat, key));
+        }
+    }
+
+    first.map(|(seat, _)| seat)
+}
+
+fn low_rank(rank: u8) -> u8 {
+    if rank == 14 { 1 } else { rank }
+}
+
+// What a set of ranks makes, then its ranks grouped by how often they show up. Comparing two
+// keys compares the made hands first (quads > full house > trips > two pair > pair > no pair),
+// then the groups, biggest first and then by rank.
+type RankKey = (u8, Vec<(usize, u8)>);
+
+fn grouped_ranks(ranks: impl Iterator<Item = u8>) -> RankKey {
+    let mut map = HashMap::new();
+    for rank in ranks {
+        *map.entry(rank).or_insert(0) += 1;
+    }
+
+    let mut results: Vec<(usize, u8)> =
+        map.into_iter().map(|(rank, count)| (count, rank)).collect();
+    results.sort_by(|a, b| b.cmp(a));
+
+    let counts: Vec<usize> = results.iter().map(|&(count, _)| count).collect();
+    let category = match counts.as_slice() {
+        [4, ..] => 5,
+        [3, 2, ..] => 4,
+        [3, ..] => 3,
+        [2, 2, ..] => 2,
+        [2, ..] => 1,
+        _ => 0,
+    };
+    (category, results)
+}
+
+fn high_key(cards: &[Card]) -> RankKey {
+    grouped_ranks(cards.iter().map(|card| card.rank))
+}
+
+// The lower the key the better the low.
+fn low_key(cards: &[Card]) -> RankKey {
+    grouped_ranks(cards.iter().map(|card| low_rank(card.rank)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PokerHandType, Suit, parse_cards};
+
+    fn stud_hand(cards: &[(u8, Suit)]) -> StudHand {
+        let mut hand = StudHand::new();
+        for &(rank, suit) in cards {
+            hand.add_card(Card::new(rank, suit).unwrap()).unwrap();
+        }
+        hand
+    }
+
+    #[test]
+    fn stud_hand_facing() {
+        let hand = stud_hand(&[
+            (2, Suit::Club),
+            (3, Suit::Club),
+            (4, Suit::Club),
+            (5, Suit::Club),
+            (6, Suit::Club),
+            (7, Suit::Club),
+            (8, Suit::Club),
+        ]);
+
+        let facing: Vec<bool> = hand.cards().iter().map(|card| card.face_up).collect();
+        assert_eq!(facing, vec![false, false, true, true, true, true, false]);
+        assert_eq!(
+            hand.up_cards(),
+            vec![
+                Card::new(4, Suit::Club).unwrap(),
+                Card::new(5, Suit::Club).unwrap(),
+                Card::new(6, Suit::Club).unwrap(),
+                Card::new(7, Suit::Club).unwrap(),
+            ]
+        );
+
+        let mut hand = hand;
+        assert!(hand.add_card(Card::new(9, Suit::Club).unwrap()).is_err());
+    }
+
+    #[test]
+    fn stud_bring_in() {
+        let cases = vec![
+            (
+                "Lowest upcard brings in",
+                vec![
+                    stud_hand(&[(14, Suit::Club), (14, Suit::Heart), (9, Suit::Heart)]),
+                    stud_hand(&[(2, Suit::Club), (2, Suit::Heart), (3, Suit::Heart)]),
+                    stud_hand(&[(5, Suit::Club), (6, Suit::Heart), (14, Suit::Spade)]),
+                ],
+                StudGame::SevenCardStud,
+                Some(1),
+            ),
+            (
+                "Suit breaks ties -- clubs are the lowest",
+                vec![
+                    stud_hand(&[(14, Suit::Club), (14, Suit::Heart), (2, Suit::Diamond)]),
+                    stud_hand(&[(10, Suit::Club), (13, Suit::Heart), (2, Suit::Club)]),
+                    stud_hand(&[(5, Suit::Club), (6, Suit::Heart), (2, Suit::Spade)]),
+                ],
+                StudGame::SevenCardStud,
+                Some(1),
+            ),
+            (
+                "Razz -- highest upcard brings in and the ace is low",
+                vec![
+                    stud_hand(&[(2, Suit::Club), (3, Suit::Heart), (14, Suit::Spade)]),
+                    stud_hand(&[(2, Suit::Heart), (3, Suit::Spade), (12, Suit::Club)]),
+                    stud_hand(&[(4, Suit::Club), (5, Suit::Heart), (8, Suit::Spade)]),
+                ],
+                StudGame::Razz,
+                Some(1),
+            ),
+            (
+                "Razz -- suit breaks ties, spades are the highest",
+                vec![
+                    stud_hand(&[(2, Suit::Club), (3, Suit::Heart), (13, Suit::Spade)]),
+                    stud_hand(&[(2, Suit::Heart), (3, Suit::Spade), (13, Suit::Heart)]),
+                ],
+                StudGame::Razz,
+                Some(0),
+            ),
+            (
+                "No upcards",
+                vec![StudHand::new()],
+                StudGame::SevenCardStud,
+                None,
+            ),
+        ];
+
+        for (name, hands, game, expected) in cases {
+            assert_eq!(bring_in(&hands, game), expected, "Case: {name}");
+        }
+    }
+
+    #[test]
+    fn stud_first_to_act() {
+        let cases = vec![
+            (
+                "Pair on board beats a higher card",
+                vec![
+                    stud_hand(&[
+                        (2, Suit::Club),
+                        (2, Suit::Heart),
+                        (14, Suit::Heart),
+                        (13, Suit::Spade),
+                    ]),
+                    stud_hand(&[
+                        (3, Suit::Club),
+                        (4, Suit::Heart),
+                        (5, Suit::Heart),
+                        (5, Suit::Spade),
+                    ]),
+                ],
+                StudGame::SevenCardStud,
+                Some(1),
+            ),
+            (
+                "Highest cards act first without a pair",
+                vec![
+                    stud_hand(&[
+                        (2, Suit::Club),
+                        (2, Suit::Heart),
+                        (14, Suit::Heart),
+                        (9, Suit::Spade),
+                    ]),
+                    stud_hand(&[
+                        (3, Suit::Club),
+                        (4, Suit::Heart),
+                        (14, Suit::Club),
+                        (10, Suit::Spade),
+                    ]),
+                ],
+                StudGame::SevenCardStud,
+                Some(1),
+            ),
+            (
+                "Ties go to the first seat",
+                vec![
+                    stud_hand(&[
+                        (2, Suit::Club),
+                        (2, Suit::Heart),
+                        (14, Suit::Heart),
+                        (9, Suit::Spade),
+                    ]),
+                    stud_hand(&[
+                        (3, Suit::Club),
+                        (4, Suit::Heart),
+                        (14, Suit::Club),
+                        (9, Suit::Club),
+                    ]),
+                ],
+                StudGame::SevenCardStud,
+                Some(0),
+            ),
+            (
+                "Razz -- lowest board acts first and pairs are bad",
+                vec![
+                    stud_hand(&[
+                        (2, Suit::Club),
+                        (2, Suit::Heart),
+                        (14, Suit::Heart),
+                        (14, Suit::Spade),
+                    ]),
+                    stud_hand(&[
+                        (3, Suit::Club),
+                        (4, Suit::Heart),
+                        (8, Suit::Club),
+                        (7, Suit::Spade),
+                    ]),
+                    stud_hand(&[
+                        (3, Suit::Heart),
+                        (4, Suit::Club),
+                        (9, Suit::Club),
+                        (2, Suit::Spade),
+                    ]),
+                ],
+                StudGame::Razz,
+                Some(1),
+            ),
+            (
+                "Two pair beats a higher pair",
+                vec![
+                    stud_hand(&[
+                        (9, Suit::Club),
+                        (9, Suit::Heart),
+                        (3, Suit::Heart),
+                        (3, Suit::Spade),
+                        (2, Suit::Club),
+                        (2, Suit::Diamond),
+                    ]),
+                    stud_hand(&[
+                        (9, Suit::Diamond),
+                        (9, Suit::Spade),
+                        (14, Suit::Heart),
+                        (14, Suit::Spade),
+                        (13, Suit::Club),
+                        (12, Suit::Diamond),
+                    ]),
+                ],
+                StudGame::SevenCardStud,
+                Some(0),
+            ),
+            (
+                "Trips beat two higher pairs",
+                vec![
+                    stud_hand(&[
+                        (9, Suit::Club),
+                        (9, Suit::Heart),
+                        (14, Suit::Heart),
+                        (14, Suit::Spade),
+                        (13, Suit::Club),
+                        (13, Suit::Diamond),
+                    ]),
+                    stud_hand(&[
+                        (9, Suit::Diamond),
+                        (9, Suit::Spade),
+                        (2, Suit::Heart),
+                        (2, Suit::Spade),
+                        (2, Suit::Club),
+                        (3, Suit::Diamond),
+                    ]),
+                ],
+                StudGame::SevenCardStud,
+                Some(1),
+            ),
+            (
+                "Razz -- a high pair acts before two low pairs",
+                vec![
+                    stud_hand(&[
+                        (9, Suit::Club),
+                        (9, Suit::Heart),
+                        (3, Suit::Heart),
+                        (3, Suit::Spade),
+                        (2, Suit::Club),
+                        (2, Suit::Diamond),
+                    ]),
+                    stud_hand(&[
+                        (9, Suit::Diamond),
+                        (9, Suit::Spade),
+                        (13, Suit::Heart),
+                        (13, Suit::Spade),
+                        (12, Suit::Club),
+                        (11, Suit::Diamond),
+                    ]),
+                ],
+                StudGame::Razz,
+                Some(1),
+            ),
+        ];
+
+        for (name, hands, game, expected) in cases {
+            assert_eq!(first_to_act(&hands, game), expected, "Case: {name}");
+        }
+    }
+
+    #[test]
+    fn stud_best_hand() {
+        let hand = stud_hand(&[
+            (2, Suit::Club),
+            (14, Suit::Heart),
+            (9, Suit::Heart),
+            (13, Suit::Heart),
+            (2, Suit::Spade),
+            (4, Suit::Heart),
+            (6, Suit::Heart),
+        ]);
+
+        let best = hand.best_hand().unwrap();
+        assert_eq!(best.poker_hand_type, Some(PokerHandType::Flush));
+        assert_eq!(
+            best.cards()
+                .iter()
+                .map(|card| card.rank)
+                .collect::<Vec<u8>>(),
+            vec![14, 13, 9, 6, 4]
+        );
+
+        assert!(stud_hand(&[(2, Suit::Club)]).best_hand().is_err());
+    }
+
+    #[test]
+    fn razz_low_cmp() {
+        let cases = vec![
+            (
+                "Wheel beats a six low",
+                stud_hand(&[
+                    (14, Suit::Club),
+                    (2, Suit::Club),
+                    (3, Suit::Club),
+                    (4, Suit::Club),
+                    (5, Suit::Club),
+                    (13, Suit::Club),
+                    (13, Suit::Heart),
+                ]),
+                stud_hand(&[
+                    (14, Suit::Heart),
+                    (2, Suit::Heart),
+                    (3, Suit::Heart),
+                    (4, Suit::Heart),
+                    (6, Suit::Heart),
+                    (6, Suit::Spade),
+                    (2, Suit::Spade),
+                ]),
+                Ordering::Greater,
+            ),
+            (
+                "Pairs lose to any unpaired low",
+                stud_hand(&[
+                    (14, Suit::Club),
+                    (14, Suit::Heart),
+                    (2, Suit::Club),
+                    (2, Suit::Heart),
+                    (3, Suit::Club),
+                    (3, Suit::Heart),
+                    (4, Suit::Club),
+                ]),
+                stud_hand(&[
+                    (13, Suit::Heart),
+                    (12, Suit::Heart),
+                    (11, Suit::Heart),
+                    (10, Suit::Heart),
+                    (9, Suit::Heart),
+                    (9, Suit::Spade),
+                    (13, Suit::Spade),
+                ]),
+                Ordering::Less,
+            ),
+            (
+                "Same lows are equal",
+                stud_hand(&[
+                    (7, Suit::Club),
+                    (5, Suit::Club),
+                    (4, Suit::Club),
+                    (3, Suit::Club),
+                    (2, Suit::Club),
+                    (13, Suit::Club),
+                    (12, Suit::Club),
+                ]),
+                stud_hand(&[
+                    (7, Suit::Heart),
+                    (5, Suit::Heart),
+                    (4, Suit::Heart),
+                    (3, Suit::Heart),
+                    (2, Suit::Heart),
+                    (13, Suit::Spade),
+                    (11, Suit::Spade),
+                ]),
+                Ordering::Equal,
+            ),
+        ];
+
+        for (name, hand_1, hand_2, expected) in cases {
+            let low_1 = hand_1.razz_low().unwrap();
+            let low_2 = hand_2.razz_low().unwrap();
+            assert_eq!(low_1.cmp(&low_2), expected, "Case: {name}");
+        }
+
+        // Made hands count before ranks, however low the ranks.
+        let five_card_cases = [
+            ("Ac Ad 2c 2d 3c", "Kc Kd Qc Jc Tc", Ordering::Less),
+            ("Ac Ad Ah 2c 3c", "Kc Kd Kh Qc Qd", Ordering::Greater),
+            ("2c 2d 3c 3d 3h", "Qc Qd Qh Kc Jc", Ordering::Less),
+            ("Ac Ad Ah As 2c", "Kc Kd Kh Qc Qd", Ordering::Less),
+        ];
+        for (low_1, low_2, expected) in five_card_cases {
+            let low_1 = RazzLow::best_of(&parse_cards(low_1).unwrap()).unwrap();
+            let low_2 = RazzLow::best_of(&parse_cards(low_2).unwrap()).unwrap();
+            assert_eq!(
+                low_1.cmp(&low_2),
+                expected,
+                "Case: {low_1:?} against {low_2:?}"
+            );
+            assert_eq!(low_1 == low_2, expected == Ordering::Equal);
+        }
+
+        let wheel = stud_hand(&[
+            (13, Suit::Club),
+            (14, Suit::Club),
+            (2, Suit::Heart),
+            (3, Suit::Club),
+            (4, Suit::Spade),
+            (5, Suit::Club),
+            (5, Suit::Heart),
+        ]);
+        assert_eq!(wheel.razz_low().unwrap().ranks(), vec![5, 4, 3, 2, 1]);
+    }
+}
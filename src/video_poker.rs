@@ -0,0 +1,625 @@
+use std::sync::OnceLock;
+
+use crate::deck::{Deck, Rng};
+use crate::{Card, PokerHand, PokerHandType, encoding, index};
+
+/// Extra pays for hands that only exist with deuces wild.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WildPays {
+    pub four_deuces: u32,
+    pub wild_royal_flush: u32,
+    pub five_of_a_kind: u32,
+}
+
+/// What a video poker machine pays per coin bet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paytable {
+    pub name: String,
+    /// Pays by hand type. Missing types pay nothing. With deuces wild, `RoyalFlush` only
+    /// means a royal flush without deuces.
+    pub pays: Vec<(PokerHandType, u32)>,
+    /// Lowest rank a pair needs to pay as `OnePair`: 11 for jacks or better.
+    pub min_pair: u8,
+    /// Four of a kind that pays by rank, as (lowest rank, highest rank, pay). Aces are 14.
+    /// Quads not covered here pay what `pays` says.
+    pub quads: Vec<(u8, u8, u32)>,
+    /// Deuces are wild when set.
+    pub deuces_wild: Option<WildPays>,
+}
+
+impl Paytable {
+    /// Full pay 9/6 Jacks or Better, 99.54% with perfect play.
+    pub fn jacks_or_better() -> Self {
+        Paytable {
+            name: "Jacks or Better 9/6".to_string(),
+            pays: vec![
+                (PokerHandType::RoyalFlush, 800),
+                (PokerHandType::StraightFlush, 50),
+                (PokerHandType::FourOfAKind, 25),
+                (PokerHandType::FullHouse, 9),
+                (PokerHandType::Flush, 6),
+                (PokerHandType::Straight, 4),
+                (PokerHandType::ThreeOfAKind, 3),
+                (PokerHandType::TwoPair, 2),
+                (PokerHandType::OnePair, 1),
+            ],
+            min_pair: 11,
+            quads: Vec::new(),
+            deuces_wild: None,
+        }
+    }
+
+    /// Full pay Deuces Wild, 100.76% with perfect play. Three of a kind is the lowest paying
+    /// hand.
+    pub fn deuces_wild() -> Self {
+        Paytable {
+            name: "Deuces Wild".to_string(),
+            pays: vec![
+                (PokerHandType::RoyalFlush, 800),
+                (PokerHandType::StraightFlush, 9),
+                (PokerHandType::FourOfAKind, 5),
+                (PokerHandType::FullHouse, 3),
+                (PokerHandType::Flush, 2),
+                (PokerHandType::Straight, 2),
+                (PokerHandType::ThreeOfAKind, 1),
+            ],
+            min_pair: 15,
+            quads: Vec::new(),
+            deuces_wild: Some(WildPays {
+                four_deuces: 200,
+                wild_royal_flush: 25,
+                five_of_a_kind: 15,
+            }),
+        }
+    }
+
+    /// 10/7 Double Bonus, 100.17% with perfect play. Four aces and four twos through fours
+    /// pay extra.
+    pub fn double_bonus() -> Self {
+        Paytable {
+            name: "Double Bonus 10/7".to_string(),
+            pays: vec![
+                (PokerHandType::RoyalFlush, 800),
+                (PokerHandType::StraightFlush, 50),
+                (PokerHandType::FourOfAKind, 50),
+                (PokerHandType::FullHouse, 10),
+                (PokerHandType::Flush, 7),
+                (PokerHandType::Straight, 5),
+                (PokerHandType::ThreeOfAKind, 3),
+                (PokerHandType::TwoPair, 1),
+                (PokerHandType::OnePair, 1),
+            ],
+            min_pair: 11,
+            quads: vec![(14, 14, 160), (2, 4, 80)],
+            deuces_wild: None,
+        }
+    }
+
+    /// What five `cards` pay per coin.
+    pub fn payout(&self, cards: &[Card]) -> Result<u32, String> {
+        if cards.len() != 5 {
+            return Err(format!(
+                "Video poker hands have 5 cards, not {}",
+                cards.len()
+            ));
+        }
+        encoding::mask(cards)?;
+
+        let numbers: Vec<u8> = cards
+            .iter()
+            .map(|&card| encoding::card_to_bits(card))
+            .collect();
+        Ok(self.pay(&numbers))
+    }
+
+    fn pays_for(&self, hand_type: &PokerHandType) -> u32 {
+        self.pays
+            .iter()
+            .find(|(paying, _)| paying == hand_type)
+            .map_or(0, |(_, pay)| *pay)
+    }
+
+    // Pay for five card numbers.
+    fn pay(&self, numbers: &[u8]) -> u32 {
+        let rank = |number: u8| number % 13 + 2;
+        let suit = |number: u8| number / 13;
+
+        let Some(wild) = &self.deuces_wild else {
+            return self.natural_pay(numbers);
+        };
+        let mut naturals = [0; 5];
+        let mut count = 0;
+        for &number in numbers.iter().filter(|&&n| rank(n) != 2) {
+            naturals[count] = number;
+            count += 1;
+        }
+        let naturals = &naturals[..count];
+        let deuces = 5 - naturals.len();
+        if deuces == 0 {
+            return self.natural_pay(numbers);
+        }
+        if deuces == 4 {
+            return wild.four_deuces;
+        }
+
+        let mut counts = [0usize; 15];
+        for &number in naturals {
+            counts[rank(number) as usize] += 1;
+        }
+        let most = counts.iter().max().copied().unwrap_or(0);
+        let pairs = counts.iter().filter(|&&count| count == 2).count();
+        let flush = naturals.iter().all(|&n| suit(n) == suit(naturals[0]));
+        // The deuces fill the gaps when the other ranks fit in five in a row.
+        let straight = most == 1
+            && (5..=14).any(|high| {
+                naturals.iter().all(|&n| {
+                    let rank = rank(n);
+                    (high - 4..=high).contains(&rank) || rank == 14 && high == 5
+                })
+            });
+
+        if flush && straight && naturals.iter().all(|&n| rank(n) >= 10) {
+            wild.wild_royal_flush
+        } else if most + deuces >= 5 {
+            wild.five_of_a_kind
+        } else if flush && straight {
+            self.pays_for(&PokerHandType::StraightFlush)
+        } else if most + deuces >= 4 {
+            self.pays_for(&PokerHandType::FourOfAKind)
+        } else if pairs == 2 {
+            self.pays_for(&PokerHandType::FullHouse)
+        } else if flush {
+            self.pays_for(&PokerHandType::Flush)
+        } else if straight {
+            self.pays_for(&PokerHandType::Straight)
+        } else if most + deuces >= 3 {
+            self.pays_for(&PokerHandType::ThreeOfAKind)
+        } else {
+            0
+        }
+    }
+
+    fn natural_pay(&self, numbers: &[u8]) -> u32 {
+        let mut ranks = [0; 5];
+        for (rank, number) in ranks.iter_mut().zip(numbers) {
+            *rank = number % 13 + 2;
+        }
+        ranks.sort_unstable_by(|a, b| b.cmp(a));
+        let flush = numbers.iter().all(|n| n / 13 == numbers[0] / 13);
+        let hand_type = patterns()[pattern_key(&ranks, flush)]
+            .as_ref()
+            .expect("Every hand has a pattern");
+
+        match hand_type {
+            PokerHandType::OnePair => {
+                let pair = ranks
+                    .windows(2)
+                    .find(|two| two[0] == two[1])
+                    .expect("A pair")[0];
+                if pair >= self.min_pair {
+                    self.pays_for(hand_type)
+                } else {
+                    0
+                }
+            }
+            PokerHandType::FourOfAKind => {
+                // The middle card of sorted quads is always one of the four.
+                let quad = ranks[2];
+                self.quads
+                    .iter()
+                    .find(|(low, high, _)| (*low..=*high).contains(&quad))
+                    .map_or_else(|| self.pays_for(hand_type), |(_, _, pay)| *pay)
+            }
+            hand_type => self.pays_for(hand_type),
+        }
+    }
+}
+
+fn pattern_key(ranks: &[u8; 5], flush: bool) -> usize {
+    ranks
+        .iter()
+        .fold(flush as usize, |key, &rank| key << 4 | rank as usize)
+}
+
+// Hand types by `pattern_key`: every way five ranks can come, sorted high to low, and all of one
+// suit or not. There are few enough that the usual five card classification sorts out each one.
+fn patterns() -> &'static [Option<PokerHandType>] {
+    static PATTERNS: OnceLock<Vec<Option<PokerHandType>>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let mut patterns = vec![None; 1 << 21];
+        let mut ranks = [14; 5];
+        loop {
+            let five_of_a_kind = ranks.iter().all(|&rank| rank == ranks[0]);
+            let distinct = ranks.windows(2).all(|two| two[0] != two[1]);
+            for flush in [false, true] {
+                if !five_of_a_kind && (distinct || !flush) {
+                    patterns[pattern_key(&ranks, flush)] = Some(pattern_type(&ranks, flush));
+                }
+            }
+
+            // The next non-increasing run of ranks, counting down from the last place.
+            let Some(i) = (0..5).rev().find(|&i| ranks[i] > 2) else {
+                break;
+            };
+            ranks[i] -= 1;
+            for j in i + 1..5 {
+                ranks[j] = ranks[i];
+            }
+        }
+        patterns
+    })
+}
+
+fn pattern_type(ranks: &[u8], flush: bool) -> PokerHandType {
+    let cards: Vec<Card> = ranks
+        .iter()
+        .enumerate()
+        .map(|(i, &rank)| {
+            let suit = if flush { 0 } else { i as u8 % 4 };
+            encoding::card_from_bits(suit * 13 + rank - 2).expect("Ranks go from 2 to 14")
+        })
+        .collect();
+    PokerHand::best_of(&cards)
+        .expect("Five cards make a hand")
+        .poker_hand_type()
+        .expect("best_of sorts the hand")
+        .clone()
+}
+
+/// One way to play a dealt hand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hold {
+    /// Bit `i` set when the `i`-th dealt card is held.
+    pub mask: u8,
+    pub cards: Vec<Card>,
+    /// Average pay per coin over every draw.
+    pub expected_value: f64,
+}
+
+/// Exact expected values for every hold of a paytable.
+///
+/// Building one goes through all 2,598,960 hands once and keeps, for every set of up to five
+/// cards, what the hands holding that set pay in total. What the hands that keep exactly the
+/// held cards and none of the discards pay then follows by inclusion-exclusion.
+pub struct Solver {
+    paytable: Paytable,
+    // `totals[k][i]`: total pay of the hands that hold the `k` cards of combination index `i`.
+    totals: Vec<Vec<u64>>,
+}
+
+// `binomials()[n][k]` is C(n, k), for colex indexes of card numbers.
+fn binomials() -> &'static [[u32; 6]; 53] {
+    static BINOMIALS: OnceLock<[[u32; 6]; 53]> = OnceLock::new();
+    BINOMIALS.get_or_init(|| {
+        let mut table = [[0; 6]; 53];
+        for (n, row) in table.iter_mut().enumerate() {
+            for (k, entry) in row.iter_mut().enumerate() {
+                *entry = encoding::binomial(n as u32, k as u32) as u32;
+            }
+        }
+        table
+    })
+}
+
+impl Solver {
+    pub fn new(paytable: Paytable) -> Self {
+        let binomials = binomials();
+        let mut totals: Vec<Vec<u64>> = (0..=5)
+            .map(|k| vec![0; binomials[52][k] as usize])
+            .collect();
+
+        for a in 0..52 {
+            for b in a + 1..52 {
+                for c in b + 1..52 {
+                    for d in c + 1..52 {
+                        for e in d + 1..52 {
+                            let numbers = [a, b, c, d, e];
+                            let pay = paytable.pay(&numbers) as u64;
+                            if pay == 0 {
+                                continue;
+                            }
+                            for (size, at) in subset_indexes(&numbers, binomials) {
+                                totals[size][at] += pay;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Solver { paytable, totals }
+    }
+
+    pub fn paytable(&self) -> &Paytable {
+        &self.paytable
+    }
+
+    /// All 32 ways to play `hand`, best first.
+    pub fn holds(&self, hand: &[Card]) -> Result<Vec<Hold>, String> {
+        if hand.len() != 5 {
+            return Err(format!(
+                "Video poker hands have 5 cards, not {}",
+                hand.len()
+            ));
+        }
+        encoding::mask(hand)?;
+
+        let numbers: Vec<u8> = hand
+            .iter()
+            .map(|&card| encoding::card_to_bits(card))
+            .collect();
+        let values = self.expected_values(&numbers);
+        let mut holds: Vec<Hold> = (0..32u8)
+            .map(|mask| Hold {
+                mask,
+                cards: (0..5)
+                    .filter(|i| mask & 1 << i != 0)
+                    .map(|i| hand[i])
+                    .collect(),
+                expected_value: values[mask as usize],
+            })
+            .collect();
+        holds.sort_by(|a, b| b.expected_value.total_cmp(&a.expected_value));
+        Ok(holds)
+    }
+
+    /// The hold with the highest expected value.
+    pub fn best_hold(&self, hand: &[Card]) -> Result<Hold, String> {
+        Ok(self.holds(hand)?.swap_remove(0))
+    }
+
+    /// Share of the money bet that comes back with perfect play, e.g. 0.9954 for 9/6 Jacks or
+    /// Better. Hands that only differ by suits play the same, so every suit pattern is solved
+    /// once.
+    pub fn expected_return(&self) -> f64 {
+        let mut total = 0.0;
+        for i in 0..index::canonical_count(5) {
+            let hand = index::canonical_hand_at(i, 5).expect("i is below canonical_count");
+            let numbers: Vec<u8> = hand
+                .iter()
+                .map(|&card| encoding::card_to_bits(card))
+                .collect();
+            let best = self
+                .expected_values(&numbers)
+                .into_iter()
+                .fold(0.0, f64::max);
+            total += best * suit_permutations(&numbers) as f64;
+        }
+        total / index::FIVE_CARD_HANDS as f64
+    }
+
+    // Expected value of each hold mask over the positions of `numbers`.
+    fn expected_values(&self, numbers: &[u8]) -> [f64; 32] {
+        // Sort so subsets come out in card order, and remember where each card came from.
+        let mut order: Vec<usize> = (0..5).collect();
+        order.sort_by_key(|&i| numbers[i]);
+        let sorted: Vec<u8> = order.iter().map(|&i| numbers[i]).collect();
+
+        let binomials = binomials();
+        let mut held_totals = [0i64; 32];
+        for (total, (size, at)) in held_totals
+            .iter_mut()
+            .zip(subset_indexes(&sorted, binomials))
+        {
+            *total = self.totals[size][at] as i64;
+        }
+
+        // Take out, one dealt card at a time, the hands that also hold that card. What is
+        // left for each hold are the hands that keep it and none of the other dealt cards.
+        for card in 0..5 {
+            for held in 0..32 {
+                if held & 1 << card == 0 {
+                    held_totals[held] -= held_totals[held | 1 << card];
+                }
+            }
+        }
+
+        let mut values = [0.0; 32];
+        for (held, total) in held_totals.iter().enumerate() {
+            let draws = binomials[47][5 - held.count_ones() as usize];
+            let mask = (0..5)
+                .filter(|&i| held & 1 << i != 0)
+                .fold(0, |mask, i| mask | 1 << order[i]);
+            values[mask] = *total as f64 / draws as f64;
+        }
+        values
+    }
+}
+
+// Size and colex index of each subset of `sorted`, bit `i` of the subset picking `sorted[i]`.
+fn subset_indexes(sorted: &[u8], binomials: &[[u32; 6]; 53]) -> [(usize, usize); 32] {
+    let mut indexes = [(0, 0); 32];
+    for subset in 1..32usize {
+        // The subset without its highest card, which comes last in colex order.
+        let highest = subset.ilog2() as usize;
+        let (size, at) = indexes[subset ^ 1 << highest];
+        indexes[subset] = (
+            size + 1,
+            at + binomials[sorted[highest] as usize][size + 1] as usize,
+        );
+    }
+    indexes
+}
+
+// How many hands turn into the same hand when suits are renamed: 24 suit orders, less the
+// ones that swap suits holding the same ranks.
+fn suit_permutations(numbers: &[u8]) -> u32 {
+    let mut masks = [0u16; 4];
+    for number in numbers {
+        masks[(number / 13) as usize] |= 1 << (number % 13);
+    }
+    masks.sort_unstable();
+
+    let mut permutations = 24;
+    let mut run = 1;
+    for i in 1..=4 {
+        if i < 4 && masks[i] == masks[i - 1] {
+            run += 1;
+        } else {
+            permutations /= (1..=run).product::<u32>();
+            run = 1;
+        }
+    }
+    permutations
+}
+
+/// Replaces the cards of `hand` not in `mask` with cards dealt from `deck`.
+pub fn draw(hand: &[Card], mask: u8, deck: &mut Deck) -> Result<Vec<Card>, String> {
+    hand.iter()
+        .enumerate()
+        .map(|(i, &card)| {
+            if mask & 1 << i != 0 {
+                Ok(card)
+            } else {
+                deck.deal()
+                    .ok_or_else(|| "The deck ran out of cards".to_string())
+            }
+        })
+        .collect()
+}
+
+/// Results of playing many hands.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Simulation {
+    pub hands: u64,
+    /// Coins paid out, betting one coin a hand.
+    pub paid: u64,
+}
+
+impl Simulation {
+    /// Share of the money bet that came back.
+    pub fn return_rate(&self) -> f64 {
+        self.paid as f64 / self.hands as f64
+    }
+}
+
+/// Plays `hands` hands from the `seed`, always taking the best hold.
+pub fn simulate(solver: &Solver, hands: u64, seed: u64) -> Result<Simulation, String> {
+    let mut rng = Rng::new(seed);
+    let mut simulation = Simulation { hands, paid: 0 };
+    for _ in 0..hands {
+        let mut deck = Deck::new();
+        deck.shuffle(&mut rng);
+        let dealt: Vec<Card> = (0..5)
+            .map(|_| deck.deal().expect("A full deck has five cards"))
+            .collect();
+        let hold = solver.best_hold(&dealt)?;
+        let drawn = draw(&dealt, hold.mask, &mut deck)?;
+        simulation.paid += solver.paytable.payout(&drawn)? as u64;
+    }
+    Ok(simulation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_cards;
+
+    fn pays(paytable: &Paytable, text: &str) -> u32 {
+        paytable.payout(&parse_cards(text).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn video_poker_payouts() {
+        let jacks = Paytable::jacks_or_better();
+        assert_eq!(pays(&jacks, "Ah Kh Qh Jh Th"), 800);
+        assert_eq!(pays(&jacks, "5h 4h 3h 2h Ah"), 50);
+        assert_eq!(pays(&jacks, "Jc Jd 7h 4s 2c"), 1);
+        assert_eq!(pays(&jacks, "Tc Td 7h 4s 2c"), 0);
+        assert_eq!(pays(&jacks, "Tc Td 7h 7s 2c"), 2);
+        assert_eq!(pays(&jacks, "Ac 2d 3h 4s 5c"), 4);
+
+        let bonus = Paytable::double_bonus();
+        assert_eq!(pays(&bonus, "Ac Ad Ah As 5c"), 160);
+        assert_eq!(pays(&bonus, "3c 3d 3h 3s 5c"), 80);
+        assert_eq!(pays(&bonus, "9c 9d 9h 9s 5c"), 50);
+        assert_eq!(pays(&bonus, "9c 9d 5h 5s 3c"), 1);
+
+        let deuces = Paytable::deuces_wild();
+        assert_eq!(pays(&deuces, "Ah Kh Qh Jh Th"), 800);
+        assert_eq!(pays(&deuces, "2c 2d 2h 2s 7c"), 200);
+        assert_eq!(pays(&deuces, "Ah Kh 2c Jh Th"), 25);
+        assert_eq!(pays(&deuces, "9c 9d 2h 2s 9h"), 15);
+        assert_eq!(pays(&deuces, "5h 4h 2c 2s Ah"), 9);
+        assert_eq!(pays(&deuces, "9c 9d 2h 5s 9h"), 5);
+        assert_eq!(pays(&deuces, "9c 9d 2h 5s 5h"), 3);
+        assert_eq!(pays(&deuces, "9h 6h 2c 4h Kh"), 2);
+        assert_eq!(pays(&deuces, "9h 8c 2c 6d 5s"), 2);
+        assert_eq!(pays(&deuces, "Kh Kc 2c 6d 8s"), 1);
+        assert_eq!(pays(&deuces, "Kh Kc 3c 3d 8s"), 0);
+
+        assert!(jacks.payout(&parse_cards("Ah Kh Qh Jh").unwrap()).is_err());
+        assert!(
+            jacks
+                .payout(&parse_cards("Ah Kh Qh Jh Ah").unwrap())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn video_poker_holds_and_return() {
+        let solver = Solver::new(Paytable::jacks_or_better());
+
+        let royal = parse_cards("Ah Kh Qh Jh Th").unwrap();
+        let best = solver.best_hold(&royal).unwrap();
+        assert_eq!((best.mask, best.expected_value), (0b11111, 800.0));
+
+        // Four to a royal: the ten of hearts, eight flushes, three straights and twelve
+        // high pairs out of 47 draws.
+        let hand = parse_cards("2c Ah Kh Qh Jh").unwrap();
+        let holds = solver.holds(&hand).unwrap();
+        assert_eq!(holds.len(), 32);
+        assert_eq!(holds[0].mask, 0b11110);
+        assert_eq!(holds[0].cards, hand[1..].to_vec());
+        assert!((holds[0].expected_value - 872.0 / 47.0).abs() < 1e-9);
+
+        // Holding nothing averages over every hand of the 47 other cards, which a brute force
+        // over a few holds agrees with.
+        let hand = parse_cards("2c 7d 9h 4s Jc").unwrap();
+        let holds = solver.holds(&hand).unwrap();
+        let jack = holds.iter().find(|hold| hold.mask == 0b10000).unwrap();
+        let mut deck = Deck::new();
+        deck.remove(&hand);
+        let mut total = 0;
+        for picked in crate::combinations(deck.len(), 4) {
+            let mut cards = vec![hand[4]];
+            cards.extend(picked.iter().map(|&i| deck.cards()[i]));
+            total += solver.paytable().payout(&cards).unwrap();
+        }
+        assert!((jack.expected_value - total as f64 / 178_365.0).abs() < 1e-9);
+
+        assert!((solver.expected_return() - 0.995439).abs() < 1e-6);
+        assert!(solver.holds(&hand[..4]).is_err());
+    }
+
+    #[test]
+    fn video_poker_deuces_wild_return() {
+        let deuces = Solver::new(Paytable::deuces_wild());
+        assert!((deuces.expected_return() - 1.007620).abs() < 1e-6);
+
+        // Four deuces: draw to nothing else.
+        let hand = parse_cards("2c 2d 2h 2s Kd").unwrap();
+        assert_eq!(deuces.best_hold(&hand).unwrap().expected_value, 200.0);
+    }
+
+    #[test]
+    fn video_poker_double_bonus_return_and_simulation() {
+        let bonus = Solver::new(Paytable::double_bonus());
+        assert!((bonus.expected_return() - 1.001725).abs() < 1e-6);
+
+        let simulation = simulate(&bonus, 2000, 43).unwrap();
+        assert_eq!(simulation.hands, 2000);
+        assert!(
+            (0.5..1.5).contains(&simulation.return_rate()),
+            "{simulation:?}"
+        );
+        assert_eq!(simulate(&bonus, 2000, 43).unwrap(), simulation);
+
+        let hand = parse_cards("2c 7d 9h 4s Jc").unwrap();
+        let mut deck = Deck::stacked(&parse_cards("Kd Qd 8c 3h").unwrap());
+        assert_eq!(
+            draw(&hand, 0b10100, &mut deck).unwrap(),
+            parse_cards("Kd Qd 9h 8c Jc").unwrap()
+        );
+    }
+}